@@ -0,0 +1,577 @@
+use crate::app::state::prices;
+use crate::util::{config_file, fmt_price};
+use hypixel::models::skyblock::BazaarProduct;
+use hypixel::util::market::BazaarFlip;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Fired alerts kept for the log view.
+pub const ALERT_LOG_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    InstantBuy,
+    InstantSell,
+    Spread,
+    /// Percent, as shown in the quick status pane.
+    SpreadPct,
+    FlipProfit,
+    /// Percent of the buy-order price.
+    FlipMargin,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 6] = [
+        Metric::InstantBuy,
+        Metric::InstantSell,
+        Metric::Spread,
+        Metric::SpreadPct,
+        Metric::FlipProfit,
+        Metric::FlipMargin,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::InstantBuy => "instant buy",
+            Metric::InstantSell => "instant sell",
+            Metric::Spread => "spread",
+            Metric::SpreadPct => "spread %",
+            Metric::FlipProfit => "flip profit",
+            Metric::FlipMargin => "flip margin",
+        }
+    }
+
+    fn is_percent(self) -> bool {
+        matches!(self, Metric::SpreadPct | Metric::FlipMargin)
+    }
+
+    /// Flip metrics are absent for products the flip filter rejected.
+    pub fn value(self, product: &BazaarProduct, flip: Option<&BazaarFlip>) -> Option<f64> {
+        match self {
+            Metric::InstantBuy => prices(product).map(|p| p.instant_buy),
+            Metric::InstantSell => prices(product).map(|p| p.instant_sell),
+            Metric::Spread => prices(product).map(|p| p.spread()),
+            Metric::SpreadPct => prices(product).map(|p| p.spread_pct()),
+            Metric::FlipProfit => flip.map(|f| f.profit_per_item),
+            Metric::FlipMargin => flip.map(|f| f.margin * 100.0),
+        }
+    }
+
+    fn format(self, v: f64) -> String {
+        if self.is_percent() {
+            format!("{v:.2}%")
+        } else {
+            fmt_price(v)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+impl Comparison {
+    pub fn label(self) -> &'static str {
+        match self {
+            Comparison::Above => "above",
+            Comparison::Below => "below",
+        }
+    }
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::Below => value < threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// `None` watches every product.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    /// Reads like the rule was typed: "ENCHANTED_DIAMOND instant sell above 1,400".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.product.as_deref().unwrap_or("any product"),
+            self.metric.label(),
+            self.comparison.label(),
+            self.metric.format(self.threshold),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    /// Unix ms of the snapshot that tripped the rule.
+    pub at: i64,
+    pub product_id: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AlertsFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<AlertRule>,
+}
+
+/// Rules plus the alerts they fired, saved to `alerts.toml` on every edit.
+#[derive(Debug, Default)]
+pub struct AlertBook {
+    rules: Vec<AlertRule>,
+    path: Option<PathBuf>,
+    /// `(rule, product)` pairs already past their threshold. A rule fires when
+    /// it starts to hold, not on every refresh while it keeps holding.
+    tripped: HashSet<(usize, String)>,
+    log: VecDeque<Alert>,
+}
+
+impl AlertBook {
+    /// Loads `alerts.toml` from the config dir. A file that can't be read
+    /// gives no rules, comes back with the error, and is left alone until a
+    /// rule is saved.
+    pub fn load_default() -> (Self, Option<io::Error>) {
+        let Some(path) = config_file("alerts.toml") else {
+            return (Self::default(), None);
+        };
+        match Self::load(path.clone()) {
+            Ok(book) => (book, None),
+            Err(e) => (
+                Self {
+                    path: Some(path),
+                    ..Self::default()
+                },
+                Some(e),
+            ),
+        }
+    }
+
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let file: AlertsFile = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(io::Error::other)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => AlertsFile::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            rules: file.rules,
+            path: Some(path),
+            ..Self::default()
+        })
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = AlertsFile {
+            rules: self.rules.clone(),
+        };
+        fs::write(path, toml::to_string(&file).map_err(io::Error::other)?)
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Newest first.
+    pub fn log(&self) -> impl Iterator<Item = &Alert> {
+        self.log.iter().rev()
+    }
+
+    /// Adds a rule, or replaces the one at `index`.
+    pub fn upsert(&mut self, index: Option<usize>, rule: AlertRule) -> io::Result<()> {
+        match index.filter(|&i| i < self.rules.len()) {
+            Some(i) => {
+                self.rules[i] = rule;
+                // Only the edited rule re-arms; the rest keep holding quietly.
+                self.tripped.retain(|(r, _)| *r != i);
+            }
+            None => self.rules.push(rule),
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        if index < self.rules.len() {
+            self.rules.remove(index);
+            // Rules past the removed one move down a slot.
+            self.tripped = std::mem::take(&mut self.tripped)
+                .into_iter()
+                .filter(|(r, _)| *r != index)
+                .map(|(r, id)| (if r > index { r - 1 } else { r }, id))
+                .collect();
+        }
+        self.save()
+    }
+
+    /// Marks every rule that already holds as tripped without firing it, so
+    /// launching with a snapshot doesn't replay alerts for old crossings.
+    pub fn seed(
+        &mut self,
+        products: &IndexMap<String, BazaarProduct>,
+        flips: &HashMap<String, BazaarFlip>,
+    ) {
+        self.tripped = self
+            .holding(products, flips)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+    }
+
+    /// Checks every rule against a snapshot, returning the alerts that just fired.
+    pub fn evaluate(
+        &mut self,
+        at: i64,
+        products: &IndexMap<String, BazaarProduct>,
+        flips: &HashMap<String, BazaarFlip>,
+    ) -> Vec<Alert> {
+        let mut fired = Vec::new();
+        let mut tripped = HashSet::new();
+        for (key, message) in self.holding(products, flips) {
+            if !self.tripped.contains(&key) {
+                fired.push(Alert {
+                    at,
+                    product_id: key.1.clone(),
+                    message,
+                });
+            }
+            tripped.insert(key);
+        }
+
+        self.tripped = tripped;
+        for alert in &fired {
+            if self.log.len() == ALERT_LOG_CAPACITY {
+                self.log.pop_front();
+            }
+            self.log.push_back(alert.clone());
+        }
+        fired
+    }
+
+    /// Every `(rule, product)` pair whose condition holds, with its message.
+    fn holding(
+        &self,
+        products: &IndexMap<String, BazaarProduct>,
+        flips: &HashMap<String, BazaarFlip>,
+    ) -> Vec<((usize, String), String)> {
+        let mut out = Vec::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            let candidates: Box<dyn Iterator<Item = &BazaarProduct>> = match &rule.product {
+                Some(id) => Box::new(products.get(id).into_iter()),
+                None => Box::new(products.values()),
+            };
+            for p in candidates {
+                let Some(value) = rule.metric.value(p, flips.get(&p.product_id)) else {
+                    continue;
+                };
+                if !rule.comparison.holds(value, rule.threshold) {
+                    continue;
+                }
+                let message = format!(
+                    "{} {} ({} {})",
+                    rule.metric.label(),
+                    rule.metric.format(value),
+                    rule.comparison.label(),
+                    rule.metric.format(rule.threshold),
+                );
+                out.push(((i, p.product_id.clone()), message));
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormField {
+    Product,
+    Metric,
+    Comparison,
+    Threshold,
+}
+
+impl FormField {
+    pub const ALL: [FormField; 4] = [
+        FormField::Product,
+        FormField::Metric,
+        FormField::Comparison,
+        FormField::Threshold,
+    ];
+}
+
+/// The add/edit popup in the alerts view.
+#[derive(Debug, Clone)]
+pub struct AlertForm {
+    /// Rule being edited; `None` adds a new one.
+    pub editing: Option<usize>,
+    pub product: String,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: String,
+    pub field: FormField,
+    pub error: Option<String>,
+}
+
+impl AlertForm {
+    pub fn new(product: Option<&str>) -> Self {
+        Self {
+            editing: None,
+            product: product.unwrap_or_default().to_string(),
+            metric: Metric::InstantSell,
+            comparison: Comparison::Above,
+            threshold: String::new(),
+            // With the product known, the threshold is what's left to type.
+            field: if product.is_some() {
+                FormField::Threshold
+            } else {
+                FormField::Product
+            },
+            error: None,
+        }
+    }
+
+    pub fn edit(index: usize, rule: &AlertRule) -> Self {
+        Self {
+            editing: Some(index),
+            product: rule.product.clone().unwrap_or_default(),
+            metric: rule.metric,
+            comparison: rule.comparison,
+            threshold: rule.threshold.to_string(),
+            field: FormField::Threshold,
+            error: None,
+        }
+    }
+
+    pub fn next_field(&mut self, delta: isize) {
+        let all = FormField::ALL;
+        let pos = all.iter().position(|f| *f == self.field).unwrap_or(0) as isize;
+        self.field = all[(pos + delta).rem_euclid(all.len() as isize) as usize];
+    }
+
+    /// Left/right on a choice field.
+    pub fn cycle(&mut self, delta: isize) {
+        match self.field {
+            FormField::Metric => {
+                let all = Metric::ALL;
+                let pos = all.iter().position(|m| *m == self.metric).unwrap_or(0) as isize;
+                self.metric = all[(pos + delta).rem_euclid(all.len() as isize) as usize];
+            }
+            FormField::Comparison => {
+                self.comparison = match self.comparison {
+                    Comparison::Above => Comparison::Below,
+                    Comparison::Below => Comparison::Above,
+                };
+            }
+            FormField::Product | FormField::Threshold => {}
+        }
+    }
+
+    pub fn input(&mut self, ch: char) {
+        match self.field {
+            FormField::Product => self.product.push(ch),
+            FormField::Threshold => self.threshold.push(ch),
+            FormField::Metric | FormField::Comparison => {}
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        match self.field {
+            FormField::Product => {
+                self.product.pop();
+            }
+            FormField::Threshold => {
+                self.threshold.pop();
+            }
+            FormField::Metric | FormField::Comparison => {}
+        }
+    }
+
+    /// Builds the rule, checking the product against the live listing.
+    pub fn to_rule(&self, products: &IndexMap<String, BazaarProduct>) -> Result<AlertRule, String> {
+        let product = match self.product.trim() {
+            "" => None,
+            typed => {
+                let id = typed.to_ascii_uppercase().replace(' ', "_");
+                if !products.contains_key(&id) {
+                    return Err(format!("unknown product {id}"));
+                }
+                Some(id)
+            }
+        };
+        let cleaned: String = self
+            .threshold
+            .chars()
+            .filter(|c| !matches!(c, ',' | '%' | ' '))
+            .collect();
+        let threshold = cleaned
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("threshold {:?} is not a number", self.threshold))?;
+        Ok(AlertRule {
+            product,
+            metric: self.metric,
+            comparison: self.comparison,
+            threshold,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hypixel::models::skyblock::BazaarOrder;
+
+    fn products(books: &[(&str, f64, f64)]) -> IndexMap<String, BazaarProduct> {
+        let order = |price| BazaarOrder {
+            amount: 1,
+            price_per_unit: price,
+            orders: 1,
+        };
+        books
+            .iter()
+            .map(|&(id, ask, bid)| {
+                let p = BazaarProduct {
+                    product_id: id.into(),
+                    buy_summary: vec![order(ask)],
+                    sell_summary: vec![order(bid)],
+                    quick_status: None,
+                };
+                (id.to_string(), p)
+            })
+            .collect()
+    }
+
+    fn book(rules: Vec<AlertRule>) -> AlertBook {
+        AlertBook {
+            rules,
+            ..AlertBook::default()
+        }
+    }
+
+    #[test]
+    fn a_rule_fires_once_per_crossing() {
+        let mut alerts = book(vec![AlertRule {
+            product: Some("ENCHANTED_DIAMOND".into()),
+            metric: Metric::InstantSell,
+            comparison: Comparison::Above,
+            threshold: 1_400.0,
+        }]);
+        let flips = HashMap::new();
+
+        let below = products(&[("ENCHANTED_DIAMOND", 1_450.0, 1_390.0)]);
+        let above = products(&[("ENCHANTED_DIAMOND", 1_450.0, 1_410.0)]);
+
+        assert!(alerts.evaluate(1, &below, &flips).is_empty());
+        assert_eq!(alerts.evaluate(2, &above, &flips).len(), 1);
+        assert!(
+            alerts.evaluate(3, &above, &flips).is_empty(),
+            "still above, no repeat"
+        );
+        assert!(alerts.evaluate(4, &below, &flips).is_empty());
+        assert_eq!(
+            alerts.evaluate(5, &above, &flips).len(),
+            1,
+            "re-armed after dropping back"
+        );
+        assert_eq!(alerts.log().count(), 2);
+    }
+
+    #[test]
+    fn editing_a_rule_leaves_the_others_tripped() {
+        let rule = |id: &str| AlertRule {
+            product: Some(id.into()),
+            metric: Metric::InstantSell,
+            comparison: Comparison::Above,
+            threshold: 100.0,
+        };
+        let mut alerts = book(vec![rule("A"), rule("B"), rule("C")]);
+        let flips = HashMap::new();
+        let snapshot = products(&[
+            ("A", 300.0, 200.0),
+            ("B", 300.0, 200.0),
+            ("C", 300.0, 200.0),
+        ]);
+        assert_eq!(alerts.evaluate(1, &snapshot, &flips).len(), 3);
+
+        alerts.upsert(Some(0), rule("A")).unwrap();
+        let fired = alerts.evaluate(2, &snapshot, &flips);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].product_id, "A");
+
+        alerts.remove(1).unwrap();
+        assert!(
+            alerts.evaluate(3, &snapshot, &flips).is_empty(),
+            "C kept its trip past the shift"
+        );
+    }
+
+    #[test]
+    fn rules_already_holding_at_launch_stay_quiet() {
+        let mut alerts = book(vec![AlertRule {
+            product: Some("ENCHANTED_DIAMOND".into()),
+            metric: Metric::InstantSell,
+            comparison: Comparison::Above,
+            threshold: 1_400.0,
+        }]);
+        let flips = HashMap::new();
+        let below = products(&[("ENCHANTED_DIAMOND", 1_450.0, 1_390.0)]);
+        let above = products(&[("ENCHANTED_DIAMOND", 1_450.0, 1_410.0)]);
+
+        alerts.seed(&above, &flips);
+
+        assert!(alerts.evaluate(1, &above, &flips).is_empty());
+        assert!(alerts.evaluate(2, &below, &flips).is_empty());
+        assert_eq!(alerts.evaluate(3, &above, &flips).len(), 1);
+    }
+
+    #[test]
+    fn a_rule_without_a_product_watches_everything() {
+        let mut alerts = book(vec![AlertRule {
+            product: None,
+            metric: Metric::SpreadPct,
+            comparison: Comparison::Above,
+            threshold: 50.0,
+        }]);
+        let snapshot = products(&[("WIDE", 200.0, 100.0), ("TIGHT", 101.0, 100.0)]);
+
+        let fired = alerts.evaluate(1, &snapshot, &HashMap::new());
+
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].product_id, "WIDE");
+    }
+
+    #[test]
+    fn the_form_accepts_typed_names_and_grouped_numbers() {
+        let snapshot = products(&[("ENCHANTED_DIAMOND", 2.0, 1.0)]);
+        let mut form = AlertForm::new(None);
+        form.product = "enchanted diamond".into();
+        form.threshold = "1,400".into();
+
+        let rule = form.to_rule(&snapshot).unwrap();
+
+        assert_eq!(rule.product.as_deref(), Some("ENCHANTED_DIAMOND"));
+        assert_eq!(rule.threshold, 1_400.0);
+
+        form.product = "nope".into();
+        assert!(form.to_rule(&snapshot).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use hypixel::models::skyblock::{BazaarOrder, BazaarProduct};
+
+/// One order book level with the running amount from the best price outward.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthLevel {
+    pub price: f64,
+    pub amount: i64,
+    pub orders: i64,
+    pub cumulative: i64,
+}
+
+/// Levels in book order (best first) with cumulative amounts.
+pub fn cumulative(levels: &[BazaarOrder]) -> Vec<DepthLevel> {
+    let mut total = 0i64;
+    levels
+        .iter()
+        .map(|o| {
+            total = total.saturating_add(o.amount);
+            DepthLevel {
+                price: o.price_per_unit,
+                amount: o.amount,
+                orders: o.orders,
+                cumulative: total,
+            }
+        })
+        .collect()
+}
+
+/// Halfway between the best ask and best bid, read off the book.
+pub fn mid_price(product: &BazaarProduct) -> Option<f64> {
+    let ask = product.buy_summary.first()?.price_per_unit;
+    let bid = product.sell_summary.first()?.price_per_unit;
+    Some((ask + bid) / 2.0)
+}
+
+/// `(price, cumulative amount)` points tracing a staircase outward from the
+/// best price: flat until the next level, then straight up by its amount.
+pub fn step_series(levels: &[DepthLevel]) -> Vec<(f64, f64)> {
+    let mut pts = Vec::with_capacity(levels.len() * 2 + 1);
+    let mut held = 0.0;
+    for l in levels {
+        pts.push((l.price, held));
+        held = l.cumulative as f64;
+        pts.push((l.price, held));
+    }
+    pts
+}
+
+/// Which way an instant order crosses the book.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Side {
+    /// Instant-buy: eats sell offers, lowest first.
+    #[default]
+    Buy,
+    /// Instant-sell: fills buy orders, highest first, and pays tax.
+    Sell,
+}
+
+impl Side {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Buy => "instant buy",
+            Self::Sell => "instant sell",
+        }
+    }
+
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Buy => Self::Sell,
+            Self::Sell => Self::Buy,
+        }
+    }
+}
+
+/// The outcome of walking the book for a quantity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    pub side: Side,
+    pub requested: i64,
+    /// How much the listed levels can absorb; short of `requested` when the
+    /// book runs out.
+    pub filled: i64,
+    /// Coins changing hands before tax.
+    pub gross: f64,
+    /// Tax withheld; only instant-sells pay it.
+    pub tax: f64,
+    /// Price of the deepest level touched.
+    pub worst_price: Option<f64>,
+    pub levels_used: usize,
+}
+
+impl Fill {
+    pub fn complete(&self) -> bool {
+        self.filled >= self.requested
+    }
+
+    /// Coins paid for a buy, or received for a sell after tax.
+    pub fn total(&self) -> f64 {
+        self.gross - self.tax
+    }
+
+    /// Per-item price over what actually filled, after tax.
+    pub fn avg_price(&self) -> Option<f64> {
+        (self.filled > 0).then(|| self.total() / self.filled as f64)
+    }
+}
+
+/// Walks the side of `product`'s book an instant order of `quantity` would
+/// hit, level by level from the best price. Sells pay `tax_rate`.
+pub fn fill(product: &BazaarProduct, side: Side, quantity: i64, tax_rate: f64) -> Fill {
+    let levels = match side {
+        Side::Buy => &product.buy_summary,
+        Side::Sell => &product.sell_summary,
+    };
+    let mut out = Fill {
+        side,
+        requested: quantity,
+        filled: 0,
+        gross: 0.0,
+        tax: 0.0,
+        worst_price: None,
+        levels_used: 0,
+    };
+    for level in levels {
+        let remaining = quantity - out.filled;
+        if remaining <= 0 {
+            break;
+        }
+        let take = remaining.min(level.amount.max(0));
+        if take == 0 {
+            continue;
+        }
+        out.filled += take;
+        out.gross += take as f64 * level.price_per_unit;
+        out.worst_price = Some(level.price_per_unit);
+        out.levels_used += 1;
+    }
+    if side == Side::Sell {
+        out.tax = out.gross * tax_rate;
+    }
+    out
+}
+
+/// The fill calculator popup: a side and a typed quantity.
+#[derive(Debug, Clone, Default)]
+pub struct FillCalc {
+    pub side: Side,
+    pub quantity: String,
+}
+
+impl FillCalc {
+    pub fn input(&mut self, ch: char) {
+        if ch.is_ascii_digit() || matches!(ch, ',' | '_' | '.' | 'k' | 'K' | 'm' | 'M') {
+            self.quantity.push(ch);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.quantity.pop();
+    }
+
+    /// The typed quantity; `50k`, `1.5m` and `50,000` all read as expected.
+    pub fn parsed(&self) -> Option<i64> {
+        parse_quantity(&self.quantity)
+    }
+}
+
+fn parse_quantity(s: &str) -> Option<i64> {
+    let s: String = s.chars().filter(|c| !matches!(c, ',' | '_')).collect();
+    let (digits, scale) = match s.chars().last()? {
+        'k' | 'K' => (&s[..s.len() - 1], 1e3),
+        'm' | 'M' => (&s[..s.len() - 1], 1e6),
+        _ => (s.as_str(), 1.0),
+    };
+    let n = (digits.parse::<f64>().ok()? * scale).round();
+    (n >= 1.0 && n < i64::MAX as f64).then_some(n as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(price: f64, amount: i64) -> BazaarOrder {
+        BazaarOrder {
+            amount,
+            price_per_unit: price,
+            orders: 1,
+        }
+    }
+
+    #[test]
+    fn cumulative_amounts_run_from_the_best_price() {
+        let asks = cumulative(&[order(10.0, 5), order(11.0, 20), order(12.0, 1)]);
+
+        let totals: Vec<i64> = asks.iter().map(|l| l.cumulative).collect();
+        assert_eq!(totals, [5, 25, 26]);
+    }
+
+    #[test]
+    fn steps_hold_flat_between_levels() {
+        let bids = cumulative(&[order(9.0, 5), order(8.0, 10)]);
+
+        assert_eq!(
+            step_series(&bids),
+            [(9.0, 0.0), (9.0, 5.0), (8.0, 5.0), (8.0, 15.0)]
+        );
+    }
+
+    fn book(asks: Vec<BazaarOrder>, bids: Vec<BazaarOrder>) -> BazaarProduct {
+        BazaarProduct {
+            product_id: "X".into(),
+            buy_summary: asks,
+            sell_summary: bids,
+            quick_status: None,
+        }
+    }
+
+    #[test]
+    fn a_large_buy_walks_up_the_asks() {
+        let p = book(vec![order(10.0, 100), order(12.0, 100)], vec![]);
+
+        let f = fill(&p, Side::Buy, 150, 0.0125);
+
+        assert!(f.complete());
+        assert_eq!(f.total(), 100.0 * 10.0 + 50.0 * 12.0);
+        assert_eq!(f.avg_price(), Some(1600.0 / 150.0));
+        assert_eq!(f.worst_price, Some(12.0));
+        assert_eq!(f.levels_used, 2);
+        assert_eq!(f.tax, 0.0);
+    }
+
+    #[test]
+    fn sells_pay_tax_and_report_a_short_book() {
+        let p = book(vec![], vec![order(9.0, 10), order(8.0, 10)]);
+
+        let f = fill(&p, Side::Sell, 50, 0.0125);
+
+        assert!(!f.complete());
+        assert_eq!(f.filled, 20);
+        assert_eq!(f.gross, 170.0);
+        assert_eq!(f.total(), 170.0 * (1.0 - 0.0125));
+    }
+
+    #[test]
+    fn quantities_accept_separators_and_suffixes() {
+        assert_eq!(parse_quantity("50,000"), Some(50_000));
+        assert_eq!(parse_quantity("50k"), Some(50_000));
+        assert_eq!(parse_quantity("1.5M"), Some(1_500_000));
+        assert_eq!(parse_quantity(""), None);
+        assert_eq!(parse_quantity("0"), None);
+        assert_eq!(parse_quantity("k"), None);
+    }
+}
@@ -0,0 +1,76 @@
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+/// Where ids that no bundled rule claims end up.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    name: String,
+    #[serde(default)]
+    ids: Vec<String>,
+    #[serde(default)]
+    prefixes: Vec<String>,
+    #[serde(default)]
+    suffixes: Vec<String>,
+}
+
+impl Rule {
+    fn claims(&self, id: &str) -> bool {
+        self.ids.iter().any(|i| i == id)
+            || self.prefixes.iter().any(|p| id.starts_with(p.as_str()))
+            || self.suffixes.iter().any(|s| id.ends_with(s.as_str()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoryFile {
+    category: Vec<Rule>,
+}
+
+static RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    toml::from_str::<CategoryFile>(include_str!("../../assets/categories.toml"))
+        .expect("bundled categories.toml is valid")
+        .category
+});
+
+/// The category of a product id; first matching rule wins.
+pub fn categorize(id: &str) -> &'static str {
+    RULES
+        .iter()
+        .find(|r| r.claims(id))
+        .map_or(UNCATEGORIZED, |r| r.name.as_str())
+}
+
+/// Every category in display order, with [`UNCATEGORIZED`] last.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    RULES
+        .iter()
+        .map(|r| r.name.as_str())
+        .chain(std::iter::once(UNCATEGORIZED))
+}
+
+/// The category `prefix` names, matched case-insensitively on its start,
+/// so `cat:ench` finds Enchantments.
+pub fn lookup(prefix: &str) -> Option<&'static str> {
+    let prefix = prefix.to_ascii_lowercase();
+    names().find(|n| n.to_ascii_lowercase().starts_with(&prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_fall_into_the_first_matching_category() {
+        assert_eq!(categorize("ENCHANTMENT_SHARPNESS_5"), "Enchantments");
+        assert_eq!(categorize("FLAWLESS_RUBY_GEM"), "Gems");
+        assert_eq!(categorize("ENCHANTED_DIAMOND"), "Mining");
+        assert_eq!(categorize("LOG_2:1"), "Foraging");
+        assert_eq!(categorize("SOMETHING_ADDED_NEXT_WEEK"), UNCATEGORIZED);
+        assert_eq!(lookup("ess"), Some("Essences"));
+        assert_eq!(lookup("uncat"), Some(UNCATEGORIZED));
+        assert_eq!(lookup("nope"), None);
+    }
+}
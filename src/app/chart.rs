@@ -0,0 +1,284 @@
+use crate::app::history::Sample;
+use crate::app::overlays::Series;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Preset spans for the history chart, ending at the latest sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChartRange {
+    FiveMinutes,
+    Hour,
+    SixHours,
+    Day,
+    Week,
+    #[default]
+    All,
+}
+
+impl ChartRange {
+    pub const ALL: [ChartRange; 6] = [
+        ChartRange::FiveMinutes,
+        ChartRange::Hour,
+        ChartRange::SixHours,
+        ChartRange::Day,
+        ChartRange::Week,
+        ChartRange::All,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartRange::FiveMinutes => "5m",
+            ChartRange::Hour => "1h",
+            ChartRange::SixHours => "6h",
+            ChartRange::Day => "24h",
+            ChartRange::Week => "7d",
+            ChartRange::All => "all",
+        }
+    }
+
+    /// `None` for everything loaded.
+    pub fn span_ms(self) -> Option<i64> {
+        const MINUTE: i64 = 60_000;
+        match self {
+            ChartRange::FiveMinutes => Some(5 * MINUTE),
+            ChartRange::Hour => Some(60 * MINUTE),
+            ChartRange::SixHours => Some(6 * 60 * MINUTE),
+            ChartRange::Day => Some(24 * 60 * MINUTE),
+            ChartRange::Week => Some(7 * 24 * 60 * MINUTE),
+            ChartRange::All => None,
+        }
+    }
+
+    /// The next wider preset, or narrower; stops at either end.
+    pub fn step(self, wider: bool) -> Self {
+        let i = Self::ALL.iter().position(|&r| r == self).unwrap_or(0);
+        let i = if wider {
+            (i + 1).min(Self::ALL.len() - 1)
+        } else {
+            i.saturating_sub(1)
+        };
+        Self::ALL[i]
+    }
+}
+
+/// Share of the visible history span kept by each chart zoom step.
+pub const CHART_ZOOM_STEP: f64 = 0.75;
+
+/// Zoom steps allowed before the window gets too narrow to read.
+pub const MAX_CHART_ZOOM: u32 = 16;
+
+/// Share of the visible span one pan step moves the chart.
+pub const PAN_STEP: f64 = 0.25;
+
+/// The stretch of history on screen, in Unix ms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl ChartWindow {
+    /// `range` ending at the latest sample, narrowed by `zoom` steps and
+    /// moved back `pan` steps, but never starting before the oldest sample.
+    pub fn new(history: &VecDeque<Sample>, range: ChartRange, zoom: u32, pan: u32) -> Option<Self> {
+        let (oldest, latest) = (history.front()?.ts, history.back()?.ts);
+        let loaded = latest - oldest;
+        let span = range.span_ms().map_or(loaded, |r| r.min(loaded));
+        let span = ((span as f64 * CHART_ZOOM_STEP.powi(zoom as i32)) as i64).max(1);
+        let back = (span as f64 * PAN_STEP * pan as f64) as i64;
+        let end = (latest - back).max(oldest + span);
+        Some(Self {
+            start: end - span,
+            end,
+        })
+    }
+
+    pub fn span_ms(&self) -> i64 {
+        self.end - self.start
+    }
+
+    /// Whether the window reaches back to the first sample.
+    pub fn at_oldest(&self, history: &VecDeque<Sample>) -> bool {
+        history.front().is_none_or(|s| self.start <= s.ts)
+    }
+
+    /// Indices of the samples inside the window, widened on the left to
+    /// at least two so there is always a line to draw.
+    pub fn visible(&self, history: &VecDeque<Sample>) -> Range<usize> {
+        let to = history.partition_point(|s| s.ts <= self.end);
+        let from = history
+            .partition_point(|s| s.ts < self.start)
+            .min(to.saturating_sub(2));
+        from..to
+    }
+}
+
+/// At most `2 * buckets` points: the low and the high of each run of
+/// samples, in time order, so spikes survive where averaging would
+/// flatten them.
+pub fn downsample(pts: &[(f64, f64)], buckets: usize) -> Series {
+    if buckets == 0 || pts.len() <= buckets * 2 {
+        return pts.to_vec();
+    }
+    let mut out = Vec::with_capacity(buckets * 2);
+    for chunk in pts.chunks(pts.len().div_ceil(buckets)) {
+        let by_value = |a: &(usize, &(f64, f64)), b: &(usize, &(f64, f64))| a.1.1.total_cmp(&b.1.1);
+        let (Some(lo), Some(hi)) = (
+            chunk.iter().enumerate().min_by(by_value),
+            chunk.iter().enumerate().max_by(by_value),
+        ) else {
+            continue;
+        };
+        let (first, second) = if lo.0 <= hi.0 { (lo, hi) } else { (hi, lo) };
+        out.push(*first.1);
+        if first.0 != second.0 {
+            out.push(*second.1);
+        }
+    }
+    out
+}
+
+/// Candle widths offered by the candlestick mode, shortest first.
+pub const CANDLE_INTERVALS: [(i64, &str); 7] = [
+    (15_000, "15s"),
+    (60_000, "1m"),
+    (5 * 60_000, "5m"),
+    (15 * 60_000, "15m"),
+    (3_600_000, "1h"),
+    (4 * 3_600_000, "4h"),
+    (24 * 3_600_000, "1d"),
+];
+
+/// The shortest interval that fits a `span_ms` window into `slots` candles.
+pub fn auto_interval(span_ms: i64, slots: usize) -> usize {
+    CANDLE_INTERVALS
+        .iter()
+        .position(|&(ms, _)| span_ms / ms < slots as i64)
+        .unwrap_or(CANDLE_INTERVALS.len() - 1)
+}
+
+/// One interval of prices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    /// Unix ms the interval starts at.
+    pub ts: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl Candle {
+    pub fn rising(&self) -> bool {
+        self.close >= self.open
+    }
+}
+
+/// Buckets time-ordered `(ts, value)` points into candles `interval_ms`
+/// wide, aligned to the clock. Intervals without a point are left out.
+pub fn candles(points: impl IntoIterator<Item = (i64, f64)>, interval_ms: i64) -> Vec<Candle> {
+    let mut out: Vec<Candle> = Vec::new();
+    for (ts, v) in points {
+        let start = ts - ts.rem_euclid(interval_ms);
+        match out.last_mut() {
+            Some(c) if c.ts == start => {
+                c.high = c.high.max(v);
+                c.low = c.low.min(v);
+                c.close = v;
+            }
+            _ => out.push(Candle {
+                ts: start,
+                open: v,
+                high: v,
+                low: v,
+                close: v,
+            }),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(ts: impl IntoIterator<Item = i64>) -> VecDeque<Sample> {
+        ts.into_iter()
+            .map(|ts| Sample {
+                ts,
+                buy: 2.0,
+                sell: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn windows_end_at_the_latest_sample_and_pan_back_to_the_oldest() {
+        // A sample a minute for two hours.
+        let h = history((0..=120).map(|m| m * 60_000));
+
+        let hour = ChartWindow::new(&h, ChartRange::Hour, 0, 0).unwrap();
+        assert_eq!((hour.start, hour.end), (60 * 60_000, 120 * 60_000));
+        assert_eq!(hour.visible(&h), 60..121);
+
+        let panned = ChartWindow::new(&h, ChartRange::Hour, 0, 2).unwrap();
+        assert_eq!(panned.end, 90 * 60_000);
+        let too_far = ChartWindow::new(&h, ChartRange::Hour, 0, 99).unwrap();
+        assert_eq!(too_far.start, 0);
+        assert!(too_far.at_oldest(&h));
+
+        // A week asked of two hours shows the two hours.
+        assert_eq!(
+            ChartWindow::new(&h, ChartRange::Week, 0, 0)
+                .unwrap()
+                .span_ms(),
+            120 * 60_000
+        );
+    }
+
+    #[test]
+    fn downsampling_keeps_each_buckets_extremes_in_order() {
+        let pts: Series = [5.0, 9.0, 1.0, 5.0, 5.0, 5.0, 0.0, 7.0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| (i as f64, v))
+            .collect();
+
+        assert_eq!(
+            downsample(&pts, 2),
+            [(1.0, 9.0), (2.0, 1.0), (6.0, 0.0), (7.0, 7.0)]
+        );
+        assert_eq!(downsample(&pts, 4), pts);
+    }
+
+    #[test]
+    fn candles_bucket_on_clock_minutes() {
+        let pts = [
+            (59_000, 5.0),
+            (60_000, 4.0),
+            (90_000, 7.0),
+            (119_999, 6.0),
+            (200_000, 1.0),
+        ];
+        let c = candles(pts, 60_000);
+
+        assert_eq!(c.len(), 3);
+        assert_eq!((c[0].ts, c[0].open, c[0].close), (0, 5.0, 5.0));
+        assert_eq!(
+            c[1],
+            Candle {
+                ts: 60_000,
+                open: 4.0,
+                high: 7.0,
+                low: 4.0,
+                close: 6.0
+            }
+        );
+        assert_eq!(c[2].ts, 180_000);
+        // A day in 120 slots needs 15m candles.
+        assert_eq!(
+            CANDLE_INTERVALS[auto_interval(24 * 3_600_000, 120)].1,
+            "15m"
+        );
+    }
+}
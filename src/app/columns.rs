@@ -0,0 +1,311 @@
+use crate::app::indicators::pressure;
+use crate::app::state::prices;
+use crate::util::config_file;
+use hypixel::models::skyblock::BazaarProduct;
+use hypixel::util::market::BazaarFlip;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// A numeric column of the product tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Buy,
+    Sell,
+    Spread,
+    SpreadPct,
+    Profit,
+    Margin,
+    Volume,
+    Imbalance,
+    Flow,
+    AvgBid,
+    AvgAsk,
+    Depth,
+}
+
+impl Column {
+    pub const ALL: [Column; 12] = [
+        Column::Buy,
+        Column::Sell,
+        Column::Spread,
+        Column::SpreadPct,
+        Column::Profit,
+        Column::Margin,
+        Column::Volume,
+        Column::Imbalance,
+        Column::Flow,
+        Column::AvgBid,
+        Column::AvgAsk,
+        Column::Depth,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Buy => "buy",
+            Column::Sell => "sell",
+            Column::Spread => "spread",
+            Column::SpreadPct => "spread %",
+            Column::Profit => "profit",
+            Column::Margin => "margin",
+            Column::Volume => "vol/wk",
+            Column::Imbalance => "imbal",
+            Column::Flow => "flow",
+            Column::AvgBid => "bid size",
+            Column::AvgAsk => "ask size",
+            Column::Depth => "depth±2%",
+        }
+    }
+
+    /// Characters the column takes, right-aligned, including its gap.
+    pub fn width(self) -> u16 {
+        match self {
+            Column::Buy | Column::Sell | Column::Spread | Column::Profit => 15,
+            Column::SpreadPct | Column::Margin => 10,
+            Column::Volume | Column::Imbalance | Column::Flow => 8,
+            Column::AvgBid | Column::AvgAsk | Column::Depth => 10,
+        }
+    }
+
+    /// The number shown, and sorted on; `None` for a missing flip or book.
+    pub fn value(self, product: &BazaarProduct, flip: Option<&BazaarFlip>) -> Option<f64> {
+        match self {
+            Column::Buy => prices(product).map(|p| p.instant_buy),
+            Column::Sell => prices(product).map(|p| p.instant_sell),
+            Column::Spread => prices(product).map(|p| p.spread()),
+            Column::SpreadPct => prices(product).map(|p| p.spread_pct()),
+            Column::Profit => flip.map(|f| f.profit_per_item),
+            Column::Margin => flip.map(|f| f.margin * 100.0),
+            Column::Volume => product
+                .quick_status
+                .as_ref()
+                .map(|q| q.buy_moving_week.min(q.sell_moving_week) as f64),
+            Column::Imbalance => pressure(product).imbalance,
+            Column::Flow => pressure(product).flow,
+            Column::AvgBid => pressure(product).avg_bid,
+            Column::AvgAsk => pressure(product).avg_ask,
+            // Both sides empty near mid usually means no book at all.
+            Column::Depth => Some(pressure(product).depth() as f64).filter(|&d| d > 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct TableFile {
+    columns: Vec<Column>,
+    sort: Option<Column>,
+    descending: bool,
+}
+
+impl Default for TableFile {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                Column::Buy,
+                Column::Sell,
+                Column::Profit,
+                Column::Margin,
+                Column::Volume,
+            ],
+            sort: None,
+            descending: true,
+        }
+    }
+}
+
+/// Which columns the product tables show, in order, and what they sort
+/// by; saved on every change.
+#[derive(Debug, Default)]
+pub struct TableLayout {
+    file: TableFile,
+    /// `None` keeps the layout in memory only.
+    path: Option<PathBuf>,
+}
+
+impl TableLayout {
+    /// Loads `columns.toml` from the config dir; a missing file is the
+    /// default layout. So is one that can't be read, which comes back with
+    /// the error and is left alone until the layout changes.
+    pub fn load_default() -> (Self, Option<io::Error>) {
+        let Some(path) = config_file("columns.toml") else {
+            return (Self::default(), None);
+        };
+        match Self::load(path.clone()) {
+            Ok(layout) => (layout, None),
+            Err(e) => (
+                Self {
+                    path: Some(path),
+                    ..Self::default()
+                },
+                Some(e),
+            ),
+        }
+    }
+
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let mut file: TableFile = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(io::Error::other)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => TableFile::default(),
+            Err(e) => return Err(e),
+        };
+        let mut seen = Vec::new();
+        file.columns.retain(|c| {
+            let fresh = !seen.contains(c);
+            seen.push(*c);
+            fresh
+        });
+        Ok(Self {
+            file,
+            path: Some(path),
+        })
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(&self.file).map_err(io::Error::other)?)
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.file.columns
+    }
+
+    /// The sort column, or `None` for search relevance.
+    pub fn sort(&self) -> Option<Column> {
+        self.file.sort
+    }
+
+    pub fn descending(&self) -> bool {
+        self.file.descending
+    }
+
+    /// A header click: highest first, then lowest first, then back to relevance.
+    pub fn click_sort(&mut self, column: Column) -> io::Result<()> {
+        match self.file.sort {
+            Some(c) if c == column && self.file.descending => self.file.descending = false,
+            Some(c) if c == column => self.file.sort = None,
+            _ => {
+                self.file.sort = Some(column);
+                self.file.descending = true;
+            }
+        }
+        self.save()
+    }
+
+    /// Relevance, then each visible column in order, then relevance again.
+    pub fn cycle_sort(&mut self) -> io::Result<()> {
+        let cols = &self.file.columns;
+        self.file.sort = match self
+            .file
+            .sort
+            .and_then(|s| cols.iter().position(|&c| c == s))
+        {
+            Some(i) => cols.get(i + 1).copied(),
+            None => cols.first().copied(),
+        };
+        self.file.descending = true;
+        self.save()
+    }
+
+    pub fn reverse_sort(&mut self) -> io::Result<()> {
+        self.file.descending = !self.file.descending;
+        self.save()
+    }
+
+    /// Shows a hidden column at the end, or hides a visible one.
+    pub fn toggle(&mut self, column: Column) -> io::Result<()> {
+        if let Some(pos) = self.file.columns.iter().position(|&c| c == column) {
+            self.file.columns.remove(pos);
+            if self.file.sort == Some(column) {
+                self.file.sort = None;
+            }
+        } else {
+            self.file.columns.push(column);
+        }
+        self.save()
+    }
+
+    /// Moves a visible column `delta` places left (negative) or right.
+    pub fn shift(&mut self, column: Column, delta: isize) -> io::Result<()> {
+        let Some(pos) = self.file.columns.iter().position(|&c| c == column) else {
+            return Ok(());
+        };
+        let to = (pos as isize + delta).clamp(0, self.file.columns.len() as isize - 1) as usize;
+        let c = self.file.columns.remove(pos);
+        self.file.columns.insert(to, c);
+        self.save()
+    }
+
+    /// Every column for the chooser: visible ones in order, then hidden ones.
+    pub fn chooser_order(&self) -> Vec<(Column, bool)> {
+        let mut out: Vec<(Column, bool)> = self.file.columns.iter().map(|&c| (c, true)).collect();
+        out.extend(
+            Column::ALL
+                .into_iter()
+                .filter(|c| !self.file.columns.contains(c))
+                .map(|c| (c, false)),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_clicks_go_high_low_then_relevance() {
+        let mut layout = TableLayout::default();
+
+        layout.click_sort(Column::Margin).unwrap();
+        assert_eq!(
+            (layout.sort(), layout.descending()),
+            (Some(Column::Margin), true)
+        );
+        layout.click_sort(Column::Margin).unwrap();
+        assert_eq!(
+            (layout.sort(), layout.descending()),
+            (Some(Column::Margin), false)
+        );
+        layout.click_sort(Column::Margin).unwrap();
+        assert_eq!(layout.sort(), None);
+    }
+
+    #[test]
+    fn layout_survives_a_restart() {
+        let path =
+            std::env::temp_dir().join(format!("bazaar-tui-columns-{}.toml", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut layout = TableLayout::load(path.clone()).unwrap();
+        layout.toggle(Column::Volume).unwrap();
+        layout.toggle(Column::SpreadPct).unwrap();
+        layout.shift(Column::SpreadPct, -10).unwrap();
+        layout.cycle_sort().unwrap();
+
+        let reloaded = TableLayout::load(path.clone()).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(
+            reloaded.columns(),
+            [
+                Column::SpreadPct,
+                Column::Buy,
+                Column::Sell,
+                Column::Profit,
+                Column::Margin
+            ]
+        );
+        assert_eq!(reloaded.sort(), Some(Column::SpreadPct));
+        assert_eq!(
+            reloaded.chooser_order()[5..7],
+            [(Column::Spread, false), (Column::Volume, false)]
+        );
+    }
+}
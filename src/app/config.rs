@@ -0,0 +1,234 @@
+use crate::app::keymap::{Keymap, KeysConfig};
+use crate::util::config_file;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Tunables read from `config.toml`; every key is optional.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Seconds between background refetches of the whole bazaar.
+    pub refresh_secs: u64,
+    /// Per-request API timeout, in seconds.
+    pub request_timeout_secs: u64,
+    /// Quiet period after typing before the search list refilters.
+    pub search_debounce_ms: u64,
+    /// Sales tax on sell offers and instant-sells, as a fraction.
+    pub tax_rate: f64,
+    /// Weekly movement needed on both sides before a flip is ranked;
+    /// without it the list is topped by unfillable one-sided books.
+    pub min_weekly_volume: i64,
+    /// Samples averaged by the chart's SMA overlay.
+    pub sma_window: usize,
+    /// Whether product charts open with the SMA overlay on.
+    pub show_sma: bool,
+    /// Samples behind the chart's EMA overlay.
+    pub ema_window: usize,
+    /// Samples behind the chart's Bollinger bands.
+    pub bollinger_window: usize,
+    /// Standard deviations from the mean to each Bollinger band.
+    pub bollinger_width: f64,
+    /// Samples in the chart's time-weighted average.
+    pub twap_window: usize,
+    /// Samples the chart's high/low envelope looks back over.
+    pub envelope_window: usize,
+    /// Whether product charts open in % mode.
+    pub show_percent: bool,
+    /// Capture the mouse for clicks and scrolling; off leaves the terminal's
+    /// own text selection working.
+    pub mouse: bool,
+    pub keys: KeysConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            refresh_secs: 3,
+            request_timeout_secs: 10,
+            search_debounce_ms: 120,
+            tax_rate: hypixel::util::market::BAZAAR_TAX,
+            min_weekly_volume: 1_000,
+            sma_window: 5,
+            show_sma: true,
+            ema_window: 10,
+            bollinger_window: 20,
+            bollinger_width: 2.0,
+            twap_window: 20,
+            envelope_window: 20,
+            show_percent: false,
+            mouse: true,
+            keys: KeysConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// `config.toml` in the config dir.
+    pub fn default_path() -> Option<PathBuf> {
+        config_file("config.toml")
+    }
+
+    /// Reads `path`; a missing file means all defaults.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let config: Self = match fs::read_to_string(path) {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e),
+        };
+        config.validate()
+    }
+
+    fn validate(self) -> io::Result<Self> {
+        let problem = if self.refresh_secs == 0 {
+            Some("refresh_secs must be at least 1".to_string())
+        } else if self.request_timeout_secs == 0 {
+            Some("request_timeout_secs must be at least 1".to_string())
+        } else if !(0.0..1.0).contains(&self.tax_rate) {
+            Some("tax_rate must be a fraction in [0, 1)".to_string())
+        } else if let Some(name) = self.zero_window() {
+            Some(format!("{name} must be at least 1"))
+        } else if self.bollinger_width.is_nan() || self.bollinger_width <= 0.0 {
+            Some("bollinger_width must be above 0".to_string())
+        } else {
+            Keymap::from_config(&self.keys)
+                .err()
+                .map(|e| format!("[keys]: {e}"))
+        };
+        match problem {
+            Some(msg) => Err(io::Error::new(io::ErrorKind::InvalidData, msg)),
+            None => Ok(self),
+        }
+    }
+
+    fn zero_window(&self) -> Option<&'static str> {
+        [
+            ("sma_window", self.sma_window),
+            ("ema_window", self.ema_window),
+            ("bollinger_window", self.bollinger_window),
+            ("twap_window", self.twap_window),
+            ("envelope_window", self.envelope_window),
+        ]
+        .into_iter()
+        .find(|&(_, n)| n == 0)
+        .map(|(name, _)| name)
+    }
+
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_secs)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    pub fn search_debounce(&self) -> Duration {
+        Duration::from_millis(self.search_debounce_ms)
+    }
+}
+
+/// Command-line flags that win over `config.toml`, including after a reload.
+#[derive(Debug, Clone, Default, Args)]
+pub struct Overrides {
+    /// Read settings from this file instead of the default config.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Seconds between refreshes
+    #[arg(long, value_name = "SECS", global = true)]
+    pub refresh_secs: Option<u64>,
+
+    /// API request timeout in seconds
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout_secs: Option<u64>,
+
+    /// Bazaar tax as a fraction, e.g. 0.0125
+    #[arg(long, value_name = "RATE", global = true)]
+    pub tax_rate: Option<f64>,
+
+    /// Weekly volume floor for flips
+    #[arg(long, value_name = "ITEMS", global = true)]
+    pub min_volume: Option<i64>,
+
+    /// SMA overlay window in samples
+    #[arg(long, value_name = "N", global = true)]
+    pub sma_window: Option<usize>,
+}
+
+impl Overrides {
+    /// The file to read: `--config`, else the default location.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.config.clone().or_else(Config::default_path)
+    }
+
+    /// Loads the config file and lays the flags over it.
+    pub fn load(&self) -> io::Result<Config> {
+        let mut config = match self.path() {
+            Some(path) => Config::load(&path)?,
+            None => Config::default(),
+        };
+        if let Some(v) = self.refresh_secs {
+            config.refresh_secs = v;
+        }
+        if let Some(v) = self.timeout_secs {
+            config.request_timeout_secs = v;
+        }
+        if let Some(v) = self.tax_rate {
+            config.tax_rate = v;
+        }
+        if let Some(v) = self.min_volume {
+            config.min_weekly_volume = v;
+        }
+        if let Some(v) = self.sma_window {
+            config.sma_window = v;
+        }
+        config.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("bazaar-tui-{}-{name}", std::process::id()));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn flags_win_over_the_file_and_unset_keys_keep_defaults() {
+        let path = temp_file("config.toml", "refresh_secs = 30\ntax_rate = 0.01\n");
+        let overrides = Overrides {
+            config: Some(path.clone()),
+            refresh_secs: Some(5),
+            ..Overrides::default()
+        };
+
+        let config = overrides.load().unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(config.refresh_secs, 5);
+        assert_eq!(config.tax_rate, 0.01);
+        assert_eq!(config.sma_window, Config::default().sma_window);
+    }
+
+    #[test]
+    fn typos_and_nonsense_values_are_rejected() {
+        let typo = temp_file("typo.toml", "refresh_sec = 3\n");
+        let zero = temp_file("zero.toml", "sma_window = 0\n");
+
+        let typo_err = Config::load(&typo).unwrap_err();
+        let zero_err = Config::load(&zero).unwrap_err();
+        fs::remove_file(typo).unwrap();
+        fs::remove_file(zero).unwrap();
+
+        assert_eq!(typo_err.kind(), io::ErrorKind::InvalidData);
+        assert!(zero_err.to_string().contains("sma_window"));
+    }
+}
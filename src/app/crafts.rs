@@ -0,0 +1,221 @@
+use crate::app::columns::Column;
+use crate::app::state::prices;
+use hypixel::models::skyblock::BazaarProduct;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// One way to turn bazaar products into another bazaar product.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recipe {
+    pub output: String,
+    #[serde(default = "one")]
+    pub count: u32,
+    pub inputs: BTreeMap<String, u32>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+struct RecipeFile {
+    recipe: Vec<Recipe>,
+}
+
+static RECIPES: LazyLock<Vec<Recipe>> = LazyLock::new(|| {
+    toml::from_str::<RecipeFile>(include_str!("../../assets/recipes.toml"))
+        .expect("bundled recipes.toml is valid")
+        .recipe
+});
+
+pub fn recipes() -> &'static [Recipe] {
+    &RECIPES
+}
+
+/// How a side of the craft trades: crossing the spread now, or waiting
+/// at the top of the book.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pricing {
+    #[default]
+    Instant,
+    Order,
+}
+
+impl Pricing {
+    pub fn flip(self) -> Self {
+        match self {
+            Pricing::Instant => Pricing::Order,
+            Pricing::Order => Pricing::Instant,
+        }
+    }
+
+    /// Unit cost of buying: the best ask now, or the best bid as a buy order.
+    pub fn buy_price(self, product: &BazaarProduct) -> Option<f64> {
+        let px = prices(product)?;
+        Some(match self {
+            Pricing::Instant => px.instant_buy,
+            Pricing::Order => px.instant_sell,
+        })
+    }
+
+    /// Unit price of selling, before tax: the best bid now, or the best
+    /// ask as a sell order.
+    pub fn sell_price(self, product: &BazaarProduct) -> Option<f64> {
+        let px = prices(product)?;
+        Some(match self {
+            Pricing::Instant => px.instant_sell,
+            Pricing::Order => px.instant_buy,
+        })
+    }
+}
+
+/// What to rank recipes by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CraftRank {
+    #[default]
+    PerCraft,
+    /// Profit per craft times the crafts an hour the thinnest market
+    /// involved could absorb.
+    PerHour,
+}
+
+/// One recipe priced against the current books.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CraftQuote {
+    /// Position in [`recipes`].
+    pub recipe: usize,
+    pub cost: f64,
+    /// Sale proceeds after tax.
+    pub revenue: f64,
+    /// Weekly volume spread over the hours of a week, divided by the
+    /// amount one craft moves, for the most limiting product.
+    pub crafts_per_hour: f64,
+}
+
+impl CraftQuote {
+    pub fn profit(&self) -> f64 {
+        self.revenue - self.cost
+    }
+
+    /// Profit as a fraction of what goes in.
+    pub fn margin(&self) -> f64 {
+        if self.cost > 0.0 {
+            self.profit() / self.cost
+        } else {
+            0.0
+        }
+    }
+
+    pub fn profit_per_hour(&self) -> f64 {
+        self.profit() * self.crafts_per_hour
+    }
+}
+
+const HOURS_PER_WEEK: f64 = 7.0 * 24.0;
+
+/// Prices a recipe; `None` when any product in it is missing or unpriced.
+pub fn quote(
+    index: usize,
+    products: &IndexMap<String, BazaarProduct>,
+    inputs: Pricing,
+    output: Pricing,
+    tax_rate: f64,
+) -> Option<CraftQuote> {
+    let recipe = recipes().get(index)?;
+    let hourly = |p: &BazaarProduct, amount: u32| {
+        Column::Volume.value(p, None).unwrap_or(0.0) / HOURS_PER_WEEK / amount as f64
+    };
+
+    let out = products.get(&recipe.output)?;
+    let revenue = output.sell_price(out)? * recipe.count as f64 * (1.0 - tax_rate);
+    let mut crafts_per_hour = hourly(out, recipe.count);
+    let mut cost = 0.0;
+    for (id, &amount) in &recipe.inputs {
+        let p = products.get(id)?;
+        cost += inputs.buy_price(p)? * amount as f64;
+        crafts_per_hour = crafts_per_hour.min(hourly(p, amount));
+    }
+    Some(CraftQuote {
+        recipe: index,
+        cost,
+        revenue,
+        crafts_per_hour,
+    })
+}
+
+/// Every priceable recipe, best first by `rank`.
+pub fn ranked(
+    products: &IndexMap<String, BazaarProduct>,
+    inputs: Pricing,
+    output: Pricing,
+    tax_rate: f64,
+    rank: CraftRank,
+) -> Vec<CraftQuote> {
+    let mut quotes: Vec<CraftQuote> = (0..recipes().len())
+        .filter_map(|i| quote(i, products, inputs, output, tax_rate))
+        .collect();
+    let key = |q: &CraftQuote| match rank {
+        CraftRank::PerCraft => q.profit(),
+        CraftRank::PerHour => q.profit_per_hour(),
+    };
+    quotes.sort_by(|a, b| key(b).total_cmp(&key(a)));
+    quotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hypixel::models::skyblock::{BazaarOrder, BazaarQuickStatus};
+
+    fn product(id: &str, ask: f64, bid: f64, weekly: i64) -> (String, BazaarProduct) {
+        let order = |price| BazaarOrder {
+            amount: 1,
+            price_per_unit: price,
+            orders: 1,
+        };
+        let product = BazaarProduct {
+            product_id: id.into(),
+            sell_summary: vec![order(bid)],
+            buy_summary: vec![order(ask)],
+            quick_status: Some(BazaarQuickStatus {
+                product_id: id.into(),
+                sell_price: bid,
+                sell_volume: 0,
+                sell_moving_week: weekly,
+                sell_orders: 0,
+                buy_price: ask,
+                buy_volume: 0,
+                buy_moving_week: weekly,
+                buy_orders: 0,
+                extra: Default::default(),
+            }),
+        };
+        (id.to_string(), product)
+    }
+
+    #[test]
+    fn a_block_craft_prices_160_inputs_against_one_taxed_output() {
+        let index = recipes()
+            .iter()
+            .position(|r| r.output == "ENCHANTED_DIAMOND_BLOCK")
+            .unwrap();
+        let products: IndexMap<String, BazaarProduct> = [
+            product("ENCHANTED_DIAMOND", 1_000.0, 990.0, 168 * 1_600),
+            product("ENCHANTED_DIAMOND_BLOCK", 170_000.0, 165_000.0, 168 * 5),
+        ]
+        .into_iter()
+        .collect();
+
+        let q = quote(index, &products, Pricing::Instant, Pricing::Instant, 0.01).unwrap();
+        assert_eq!(q.cost, 160_000.0);
+        assert_eq!(q.revenue, 165_000.0 * 0.99);
+        // Five blocks an hour sell; ten crafts' worth of diamonds trade.
+        assert_eq!(q.crafts_per_hour, 5.0);
+
+        let q = quote(index, &products, Pricing::Order, Pricing::Order, 0.0).unwrap();
+        assert_eq!(q.profit(), 170_000.0 - 158_400.0);
+    }
+}
@@ -0,0 +1,266 @@
+use hypixel::models::skyblock::BazaarProduct;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::app::state::{Prices, prices};
+
+/// Samples kept per product, on disk and in an open chart: enough for the
+/// 7d chart range if the price moved on every 3s poll.
+pub const HISTORY_CAPACITY: usize = 7 * 24 * 60 * 20;
+
+/// A product's file is cut back to its newest [`HISTORY_CAPACITY`] samples
+/// once it passes this size. Lines run about 30 bytes, so that is roughly
+/// twice a full file.
+const COMPACT_BYTES: u64 = HISTORY_CAPACITY as u64 * 64;
+
+/// One price observation, stamped with the API's wall-clock `lastUpdated`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Unix milliseconds.
+    pub ts: i64,
+    pub buy: f64,
+    pub sell: f64,
+}
+
+impl Sample {
+    pub fn prices(&self) -> Prices {
+        Prices {
+            instant_buy: self.buy,
+            instant_sell: self.sell,
+        }
+    }
+}
+
+/// Append-only price log with one `ts,buy,sell` file per product.
+///
+/// Plain text keeps the files greppable and lets a crash lose at most the
+/// line being written.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    /// The store under the platform data dir, if there is one and it is writable.
+    pub fn open_default() -> Option<Self> {
+        let dir = dirs::data_dir()?.join("hypixel-bazaar-tui").join("history");
+        Self::open(dir).ok()
+    }
+
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, product_id: &str) -> PathBuf {
+        // Ids like `INK_SACK:3` are not valid file names everywhere.
+        let name: String = product_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{name}.csv"))
+    }
+
+    pub fn append(&self, product_id: &str, samples: &[Sample]) -> io::Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(product_id))?;
+        file.write_all(to_lines(samples).as_bytes())?;
+        let size = file.metadata()?.len();
+        drop(file);
+        if size > COMPACT_BYTES {
+            self.compact(product_id, HISTORY_CAPACITY)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites a product's file with only its newest `keep` samples. The
+    /// copy is renamed over the original, so a crash leaves one or the other.
+    fn compact(&self, product_id: &str, keep: usize) -> io::Result<()> {
+        let samples = self.load(product_id, keep)?;
+        let path = self.path(product_id);
+        let tmp = path.with_extension("csv.tmp");
+        fs::write(&tmp, to_lines(&samples))?;
+        fs::rename(tmp, path)
+    }
+
+    /// The newest `limit` samples, oldest first. A missing file is empty history.
+    pub fn load(&self, product_id: &str, limit: usize) -> io::Result<Vec<Sample>> {
+        let file = match File::open(self.path(product_id)) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut tail = VecDeque::with_capacity(limit.min(4096));
+        for line in BufReader::new(file).lines() {
+            // Skips a torn final line rather than failing the whole load.
+            let Some(sample) = parse_line(&line?) else {
+                continue;
+            };
+            if tail.len() == limit {
+                tail.pop_front();
+            }
+            if limit > 0 {
+                tail.push_back(sample);
+            }
+        }
+        Ok(tail.into())
+    }
+}
+
+fn to_lines(samples: &[Sample]) -> String {
+    let mut out = String::new();
+    for s in samples {
+        out.push_str(&format!("{},{},{}\n", s.ts, s.buy, s.sell));
+    }
+    out
+}
+
+fn parse_line(line: &str) -> Option<Sample> {
+    let mut fields = line.trim().split(',');
+    let sample = Sample {
+        ts: fields.next()?.parse().ok()?,
+        buy: fields.next()?.parse().ok()?,
+        sell: fields.next()?.parse().ok()?,
+    };
+    Some(sample)
+}
+
+/// Feeds snapshots into a [`HistoryStore`], writing only prices that moved.
+///
+/// Repeated polls often return the same snapshot, and most products sit still
+/// between snapshots, so logging every poll would mostly duplicate lines.
+#[derive(Debug)]
+pub struct HistoryWriter {
+    store: HistoryStore,
+    last: HashMap<String, Sample>,
+}
+
+impl HistoryWriter {
+    pub fn new(store: HistoryStore) -> Self {
+        Self {
+            store,
+            last: HashMap::new(),
+        }
+    }
+
+    pub fn record<'a>(
+        &mut self,
+        ts: i64,
+        products: impl IntoIterator<Item = &'a BazaarProduct>,
+    ) -> io::Result<()> {
+        for p in products {
+            let Some(px) = prices(p) else {
+                continue;
+            };
+            let sample = Sample {
+                ts,
+                buy: px.instant_buy,
+                sell: px.instant_sell,
+            };
+            if let Some(prev) = self.last.get(&p.product_id)
+                && (prev.ts >= ts || (prev.buy == sample.buy && prev.sell == sample.sell))
+            {
+                continue;
+            }
+            self.store.append(&p.product_id, &[sample])?;
+            self.last.insert(p.product_id.clone(), sample);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hypixel::models::skyblock::BazaarOrder;
+
+    fn temp_store(name: &str) -> HistoryStore {
+        let dir =
+            std::env::temp_dir().join(format!("bazaar-tui-history-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        HistoryStore::open(dir).unwrap()
+    }
+
+    fn product(id: &str, ask: f64, bid: f64) -> BazaarProduct {
+        let order = |price| BazaarOrder {
+            amount: 1,
+            price_per_unit: price,
+            orders: 1,
+        };
+        BazaarProduct {
+            product_id: id.into(),
+            buy_summary: vec![order(ask)],
+            sell_summary: vec![order(bid)],
+            quick_status: None,
+        }
+    }
+
+    #[test]
+    fn load_returns_the_newest_samples_in_order() {
+        let store = temp_store("tail");
+        let samples: Vec<Sample> = (0..10)
+            .map(|i| Sample {
+                ts: i,
+                buy: i as f64 + 0.5,
+                sell: i as f64,
+            })
+            .collect();
+        store.append("INK_SACK:3", &samples).unwrap();
+
+        let loaded = store.load("INK_SACK:3", 3).unwrap();
+
+        assert_eq!(loaded, samples[7..]);
+        assert!(store.load("NEVER_SEEN", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn compacting_keeps_the_newest_samples() {
+        let store = temp_store("compact");
+        let sample = |ts| Sample {
+            ts,
+            buy: 2.0,
+            sell: 1.0,
+        };
+        store
+            .append("A", &(0..10).map(sample).collect::<Vec<_>>())
+            .unwrap();
+
+        store.compact("A", 3).unwrap();
+        store.append("A", &[sample(10)]).unwrap();
+
+        let ts: Vec<i64> = store.load("A", 100).unwrap().iter().map(|s| s.ts).collect();
+        assert_eq!(ts, [7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn the_writer_skips_repeats_and_unchanged_prices() {
+        let store = temp_store("dedupe");
+        let mut writer = HistoryWriter::new(store.clone());
+        let moving = product("MOVING", 2.0, 1.0);
+        let still = product("STILL", 2.0, 1.0);
+
+        writer.record(1, [&moving, &still]).unwrap();
+        writer.record(1, [&product("MOVING", 3.0, 1.0)]).unwrap();
+        writer
+            .record(2, [&product("MOVING", 3.0, 1.0), &still])
+            .unwrap();
+
+        assert_eq!(store.load("MOVING", 10).unwrap().len(), 2);
+        assert_eq!(store.load("STILL", 10).unwrap().len(), 1);
+    }
+}
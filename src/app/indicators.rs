@@ -0,0 +1,108 @@
+use crate::app::book::mid_price;
+use hypixel::models::skyblock::BazaarProduct;
+
+/// Band around the mid price that [`Pressure::bid_depth`] and
+/// [`Pressure::ask_depth`] count, in percent.
+pub const DEPTH_BAND_PCT: f64 = 2.0;
+
+/// Who is leaning on a product's book, derived from `quick_status` and the
+/// listed levels. Every ratio is `None` when its denominator is zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pressure {
+    /// Resting buy-order volume minus sell-offer volume, over their sum:
+    /// +1 is all bids, -1 all asks.
+    pub imbalance: Option<f64>,
+    /// Items instant-bought per item instant-sold over the past week; above
+    /// 1 buyers are taking more than sellers are dumping.
+    pub flow: Option<f64>,
+    /// Mean items per buy order.
+    pub avg_bid: Option<f64>,
+    /// Mean items per sell offer.
+    pub avg_ask: Option<f64>,
+    /// Listed bid items within [`DEPTH_BAND_PCT`] of mid.
+    pub bid_depth: i64,
+    /// Listed ask items within [`DEPTH_BAND_PCT`] of mid.
+    pub ask_depth: i64,
+}
+
+impl Pressure {
+    pub fn depth(&self) -> i64 {
+        self.bid_depth + self.ask_depth
+    }
+}
+
+fn ratio(num: f64, den: f64) -> Option<f64> {
+    (den > 0.0).then(|| num / den)
+}
+
+pub fn pressure(product: &BazaarProduct) -> Pressure {
+    let mut p = Pressure::default();
+    // `buy_*` fields describe the ask side (what an instant buy eats),
+    // `sell_*` the bid side.
+    if let Some(q) = &product.quick_status {
+        let (bids, asks) = (q.sell_volume as f64, q.buy_volume as f64);
+        p.imbalance = ratio(bids - asks, bids + asks);
+        p.flow = ratio(q.buy_moving_week as f64, q.sell_moving_week as f64);
+        p.avg_bid = ratio(bids, q.sell_orders as f64);
+        p.avg_ask = ratio(asks, q.buy_orders as f64);
+    }
+    if let Some(mid) = mid_price(product) {
+        let band = mid * DEPTH_BAND_PCT / 100.0;
+        p.bid_depth = product
+            .sell_summary
+            .iter()
+            .take_while(|o| o.price_per_unit >= mid - band)
+            .map(|o| o.amount)
+            .sum();
+        p.ask_depth = product
+            .buy_summary
+            .iter()
+            .take_while(|o| o.price_per_unit <= mid + band)
+            .map(|o| o.amount)
+            .sum();
+    }
+    p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hypixel::models::skyblock::{BazaarOrder, BazaarQuickStatus};
+
+    fn order(price: f64, amount: i64) -> BazaarOrder {
+        BazaarOrder {
+            amount,
+            price_per_unit: price,
+            orders: 1,
+        }
+    }
+
+    #[test]
+    fn a_bid_heavy_book_leans_positive() {
+        let product = BazaarProduct {
+            product_id: "X".into(),
+            // Mid is 100; the 2% band runs 98..=102.
+            buy_summary: vec![order(101.0, 10), order(102.0, 5), order(110.0, 1_000)],
+            sell_summary: vec![order(99.0, 40), order(97.0, 1_000)],
+            quick_status: Some(BazaarQuickStatus {
+                product_id: "X".into(),
+                buy_price: 101.0,
+                sell_price: 99.0,
+                buy_volume: 1_000,
+                sell_volume: 3_000,
+                buy_moving_week: 500,
+                sell_moving_week: 250,
+                buy_orders: 10,
+                sell_orders: 0,
+                extra: Default::default(),
+            }),
+        };
+
+        let p = pressure(&product);
+        assert_eq!(p.imbalance, Some(0.5));
+        assert_eq!(p.flow, Some(2.0));
+        assert_eq!(p.avg_ask, Some(100.0));
+        assert_eq!(p.avg_bid, None);
+        assert_eq!((p.bid_depth, p.ask_depth), (40, 15));
+    }
+}
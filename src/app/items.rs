@@ -0,0 +1,128 @@
+use crate::util::pretty_name;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+    Mythic,
+    Divine,
+    Special,
+}
+
+impl Rarity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Rarity::Common => "COMMON",
+            Rarity::Uncommon => "UNCOMMON",
+            Rarity::Rare => "RARE",
+            Rarity::Epic => "EPIC",
+            Rarity::Legendary => "LEGENDARY",
+            Rarity::Mythic => "MYTHIC",
+            Rarity::Divine => "DIVINE",
+            Rarity::Special => "SPECIAL",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Entry {
+    name: Option<String>,
+    rarity: Option<Rarity>,
+    aliases: Vec<String>,
+}
+
+static ITEMS: LazyLock<HashMap<String, Entry>> = LazyLock::new(|| {
+    toml::from_str(include_str!("../../assets/items.toml")).expect("bundled items.toml is valid")
+});
+
+/// What the index knows about a product beyond its id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemInfo {
+    pub name: String,
+    pub rarity: Option<Rarity>,
+    /// Extra search terms, not normalized.
+    pub aliases: Vec<String>,
+}
+
+/// Metadata for `id` from the bundled table, filling gaps from the id
+/// itself; `pretty_name` is the last resort for the name.
+pub fn lookup(id: &str) -> ItemInfo {
+    let entry = ITEMS.get(id);
+    let mut aliases = entry.map(|e| e.aliases.clone()).unwrap_or_default();
+
+    let name = match entry.and_then(|e| e.name.clone()) {
+        Some(name) => name,
+        None => match id.strip_prefix("ENCHANTMENT_") {
+            // Books are searched by their number as often as the numeral.
+            Some(rest) => {
+                aliases.push(pretty_name(rest));
+                enchantment_name(rest)
+            }
+            None => match id.strip_prefix("ESSENCE_") {
+                Some(kind) => format!("{} Essence", pretty_name(kind)),
+                None => pretty_name(id),
+            },
+        },
+    };
+    if let Some(base) = name.strip_prefix("Enchanted ") {
+        aliases.push(format!("e {base}"));
+    }
+
+    ItemInfo {
+        name,
+        rarity: entry.and_then(|e| e.rarity),
+        aliases,
+    }
+}
+
+/// `ULTIMATE_WISE_5` as the game shows it, `Ultimate Wise V`.
+fn enchantment_name(rest: &str) -> String {
+    let Some((base, level)) = rest.rsplit_once('_') else {
+        return pretty_name(rest);
+    };
+    match level.parse::<usize>().ok().and_then(roman) {
+        Some(numeral) => format!("{} {numeral}", pretty_name(base)),
+        None => pretty_name(rest),
+    }
+}
+
+fn roman(n: usize) -> Option<&'static str> {
+    const NUMERALS: [&str; 10] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+    NUMERALS.get(n.checked_sub(1)?).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_come_from_the_table_then_the_id() {
+        assert_eq!(lookup("INK_SACK:3").name, "Cocoa Beans");
+        assert_eq!(lookup("NETHER_STALK").rarity, Some(Rarity::Common));
+
+        let wise = lookup("ENCHANTMENT_ULTIMATE_WISE_5");
+        assert_eq!(wise.name, "Ultimate Wise V");
+        assert_eq!(wise.aliases, ["Ultimate Wise 5"]);
+
+        assert_eq!(lookup("ESSENCE_WITHER").name, "Wither Essence");
+        assert_eq!(lookup("SOMETHING_NEW:2").name, "Something New (2)");
+        assert!(
+            lookup("BOOSTER_COOKIE")
+                .aliases
+                .contains(&"cookie".to_string())
+        );
+        assert!(
+            lookup("ENCHANTED_DIAMOND")
+                .aliases
+                .contains(&"e Diamond".to_string())
+        );
+    }
+}
@@ -0,0 +1,946 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Everything a key can be bound to, outside of text fields and popups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    ReloadConfig,
+    /// Leave the view; on the search screen, clear the query or quit.
+    Back,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Open,
+    /// Put the search box back into typing mode.
+    FocusSearch,
+    CycleSort,
+    ReverseSort,
+    /// Open the column chooser.
+    Columns,
+    PrevCategory,
+    NextCategory,
+    /// Craft inputs at instant buy or buy order.
+    InputPricing,
+    /// Craft outputs at instant sell or sell order.
+    OutputPricing,
+    ToggleStar,
+    /// Mark or unmark a product for the compare view.
+    ToggleMark,
+    /// Open the compare view with the marked products.
+    Compare,
+    NextView,
+    TogglePercent,
+    ToggleSma,
+    ToggleMidline,
+    ToggleEma,
+    ToggleBollinger,
+    /// The time-weighted average overlay.
+    ToggleTwap,
+    /// The high/low envelope overlay.
+    ToggleEnvelope,
+    /// The spread-over-time pane under the price panes.
+    ToggleSpread,
+    /// Narrower preset chart range.
+    PrevRange,
+    /// Wider preset chart range.
+    NextRange,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    /// OHLC candles in place of the price lines.
+    ToggleCandles,
+    CandleInterval,
+    ToggleDepth,
+    FillCalc,
+    NewAlert,
+    EditAlert,
+    DeleteAlert,
+    Refresh,
+    Help,
+}
+
+impl Action {
+    /// One line for the help overlay.
+    pub fn describe(self, context: Context) -> &'static str {
+        match (self, context) {
+            (Self::Quit, _) => "Quit",
+            (Self::ReloadConfig, _) => "Re-read config.toml",
+            (Self::Back, Context::Search) => "Clear the query, or quit when it is empty",
+            (Self::Back, Context::Detail) => "Back to the previous view",
+            (Self::Back, Context::Crafts) => "Leave the breakdown, or back to search",
+            (Self::Back, _) => "Back to search",
+            (Self::Up | Self::Down, Context::Detail) => "Scroll the order book (depth on)",
+            (Self::PageUp | Self::PageDown, Context::Detail) => "Scroll the order book a page",
+            (Self::Top, Context::Detail) => "Recentre the order book on the mid price",
+            (Self::Left, Context::Compare) => "Select the product to the left",
+            (Self::Right, Context::Compare) => "Select the product to the right",
+            (Self::Left, _) => "Move left",
+            (Self::Right, _) => "Move right",
+            (Self::Up, _) => "Move up",
+            (Self::Down, _) => "Move down",
+            (Self::PageUp, _) => "Move up a page",
+            (Self::PageDown, _) => "Move down a page",
+            (Self::Top, _) => "Jump to the first row",
+            (Self::Bottom, _) => "Jump to the last row",
+            (Self::Open, Context::Crafts) => "Break the recipe down, then open a part's book",
+            (Self::Open, Context::Compare) => "Open the selected product",
+            (Self::Open, _) => "Open the product",
+            (Self::FocusSearch, _) => "Type in the search box",
+            (Self::CycleSort, Context::Crafts) => "Rank by profit per craft / per hour",
+            (Self::CycleSort, _) => "Sort by the next column, then relevance",
+            (Self::ReverseSort, _) => "Flip the sort between high and low first",
+            (Self::Columns, _) => "Show, hide and reorder columns",
+            (Self::PrevCategory, _) => "Previous category tab",
+            (Self::NextCategory, _) => "Next category tab",
+            (Self::InputPricing, _) => "Price inputs at instant buy / buy order",
+            (Self::OutputPricing, _) => "Price output at instant sell / sell order",
+            (Self::ToggleStar, Context::Watchlist) => "Unstar the product",
+            (Self::ToggleStar, _) => "Star / unstar the product",
+            (Self::ToggleMark, Context::Compare) => "Drop the selected product",
+            (Self::ToggleMark, _) => "Mark / unmark the product for comparing",
+            (Self::Compare, _) => "Compare the marked products",
+            (Self::NextView, Context::Search) => "Open the watchlist",
+            (Self::NextView, Context::Watchlist) => "Open alerts",
+            (Self::NextView, Context::Alerts) => "Open crafts",
+            (Self::NextView, Context::Crafts) => "Open NPC flips",
+            (Self::NextView, _) => "Back to search",
+            (Self::TogglePercent, _) => "Chart in % change / absolute price",
+            (Self::ToggleSma, _) => "Toggle the SMA overlay",
+            (Self::ToggleMidline, _) => "Toggle the mean line",
+            (Self::ToggleEma, _) => "Toggle the EMA overlay",
+            (Self::ToggleBollinger, _) => "Toggle the Bollinger bands",
+            (Self::ToggleTwap, _) => "Toggle the time-weighted average",
+            (Self::ToggleEnvelope, _) => "Toggle the high/low envelope",
+            (Self::ToggleSpread, _) => "Toggle the spread history pane",
+            (Self::PrevRange, _) => "Narrower chart range (5m … all)",
+            (Self::NextRange, _) => "Wider chart range (5m … all)",
+            (Self::ZoomIn, _) => "Zoom the chart in",
+            (Self::ZoomOut, _) => "Zoom the chart out",
+            (Self::PanLeft, _) => "Pan the chart back in time",
+            (Self::PanRight, _) => "Pan the chart towards now",
+            (Self::ToggleCandles, _) => "Toggle candlesticks",
+            (Self::CandleInterval, _) => "Next candle interval (auto, 15s … 1d)",
+            (Self::ToggleDepth, _) => "Toggle the full order book and depth chart",
+            (Self::FillCalc, _) => "Fill cost calculator for a quantity",
+            (Self::NewAlert, _) => "New alert rule",
+            (Self::EditAlert, _) => "Edit the selected rule",
+            (Self::DeleteAlert, _) => "Delete the selected rule",
+            (Self::Refresh, _) => "Refetch now",
+            (Self::Help, _) => "Show this help",
+        }
+    }
+}
+
+/// The name used in `config.toml`: `toggle_sma`.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => f.write_str(&name),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+/// Where a binding applies. `Global` bindings work everywhere unless the
+/// view binds the same key itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Global,
+    Search,
+    Detail,
+    Watchlist,
+    Alerts,
+    Crafts,
+    Npc,
+    Compare,
+}
+
+impl Context {
+    /// The `[keys]` sub-table that rebinds this context.
+    fn table(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Search => "search",
+            Self::Detail => "detail",
+            Self::Watchlist => "watchlist",
+            Self::Alerts => "alerts",
+            Self::Crafts => "crafts",
+            Self::Npc => "npc",
+            Self::Compare => "compare",
+        }
+    }
+}
+
+/// One key press, as written in the config: `j`, `G`, `ctrl+s`, `pagedown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    mods: KeyModifiers,
+}
+
+impl Key {
+    const fn new(code: KeyCode, mods: KeyModifiers) -> Self {
+        Self { code, mods }
+    }
+
+    pub fn from_event(ev: KeyEvent) -> Self {
+        let mut mods =
+            ev.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        // `G` and BackTab already say shift; terminals disagree on whether
+        // to set it too.
+        if matches!(ev.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            mods.remove(KeyModifiers::SHIFT);
+        }
+        Self::new(ev.code, mods)
+    }
+
+    /// The character a text field would type for this key.
+    pub fn as_char(&self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c)
+                if !self
+                    .mods
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    /// An unmodified printable key, which text fields would swallow.
+    pub fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.mods.is_empty()
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let mut mods = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((m, tail)) = rest.split_once('+').filter(|(_, t)| !t.is_empty()) {
+            match m.to_ascii_lowercase().as_str() {
+                "ctrl" => mods |= KeyModifiers::CONTROL,
+                "alt" => mods |= KeyModifiers::ALT,
+                "shift" => mods |= KeyModifiers::SHIFT,
+                _ => return None,
+            }
+            rest = tail;
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "space" => KeyCode::Char(' '),
+            lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => {
+                    let mut chars = rest.chars();
+                    let c = chars.next()?;
+                    if chars.next().is_some() {
+                        return None;
+                    }
+                    // Crossterm reports ctrl+S as ctrl+s, and plain shift+g as G.
+                    let c = if mods.contains(KeyModifiers::CONTROL) {
+                        c.to_ascii_lowercase()
+                    } else if mods.contains(KeyModifiers::SHIFT) {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c
+                    };
+                    mods.remove(KeyModifiers::SHIFT);
+                    KeyCode::Char(c)
+                }
+            },
+        };
+        Some(Self::new(code, mods))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mods.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.mods.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.mods.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.mods.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Backspace => write!(f, "Bksp"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// Parses one binding: a single key, or a sequence such as `gg` or `g t`.
+fn parse_sequence(s: &str) -> Option<Vec<Key>> {
+    if let Some(key) = Key::parse(s) {
+        return Some(vec![key]);
+    }
+    if s.contains(' ') {
+        return s.split_whitespace().map(Key::parse).collect();
+    }
+    if s.contains('+') {
+        return None;
+    }
+    s.chars()
+        .map(|c| Some(Key::new(KeyCode::Char(c), KeyModifiers::NONE)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    #[default]
+    Default,
+    /// Adds `j`/`k`, `gg`/`G`, `ctrl+u`/`ctrl+d` and `/` to focus search.
+    Vim,
+}
+
+/// The `[keys]` table: a preset plus per-view rebinds. Naming an action
+/// replaces all of its keys in that view.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: Preset,
+    pub global: BTreeMap<Action, Vec<String>>,
+    pub search: BTreeMap<Action, Vec<String>>,
+    pub detail: BTreeMap<Action, Vec<String>>,
+    pub watchlist: BTreeMap<Action, Vec<String>>,
+    pub alerts: BTreeMap<Action, Vec<String>>,
+    pub crafts: BTreeMap<Action, Vec<String>>,
+    pub npc: BTreeMap<Action, Vec<String>>,
+    pub compare: BTreeMap<Action, Vec<String>>,
+}
+
+type Table = &'static [(Action, &'static [&'static str])];
+
+const GLOBAL: Table = &[
+    (Action::Quit, &["ctrl+c"]),
+    (Action::ReloadConfig, &["f5"]),
+    (Action::Help, &["?", "f1"]),
+];
+
+const SEARCH: Table = &[
+    (Action::Back, &["esc"]),
+    (Action::Open, &["enter"]),
+    (Action::Up, &["up"]),
+    (Action::Down, &["down"]),
+    (Action::PageUp, &["pageup"]),
+    (Action::PageDown, &["pagedown"]),
+    (Action::Top, &["home", "ctrl+up"]),
+    (Action::Bottom, &["end", "ctrl+down"]),
+    (Action::CycleSort, &["ctrl+s"]),
+    (Action::ReverseSort, &["ctrl+r"]),
+    (Action::Columns, &["ctrl+o"]),
+    (Action::PrevCategory, &["ctrl+left"]),
+    (Action::NextCategory, &["ctrl+right"]),
+    (Action::ToggleStar, &["ctrl+f"]),
+    (Action::ToggleMark, &["ctrl+x"]),
+    (Action::Compare, &["ctrl+p"]),
+    (Action::NextView, &["tab"]),
+];
+
+const DETAIL: Table = &[
+    (Action::Back, &["b", "esc"]),
+    (Action::Refresh, &["r"]),
+    (Action::TogglePercent, &["p"]),
+    (Action::ToggleSma, &["m"]),
+    (Action::ToggleMidline, &["g"]),
+    (Action::ToggleEma, &["e"]),
+    (Action::ToggleBollinger, &["B"]),
+    (Action::ToggleTwap, &["t"]),
+    (Action::ToggleEnvelope, &["x"]),
+    (Action::ToggleSpread, &["s"]),
+    (Action::PrevRange, &["["]),
+    (Action::NextRange, &["]"]),
+    (Action::ZoomIn, &["+", "="]),
+    (Action::ZoomOut, &["-"]),
+    (Action::PanLeft, &["left"]),
+    (Action::PanRight, &["right"]),
+    (Action::ToggleCandles, &["o"]),
+    (Action::CandleInterval, &["i"]),
+    (Action::ToggleDepth, &["d"]),
+    (Action::ToggleStar, &["f"]),
+    (Action::FillCalc, &["c"]),
+    (Action::NewAlert, &["a"]),
+    (Action::Up, &["up"]),
+    (Action::Down, &["down"]),
+    (Action::PageUp, &["pageup"]),
+    (Action::PageDown, &["pagedown"]),
+    (Action::Top, &["home"]),
+];
+
+const WATCHLIST: Table = &[
+    (Action::Back, &["esc"]),
+    (Action::NextView, &["tab"]),
+    (Action::Open, &["enter"]),
+    (Action::Up, &["up"]),
+    (Action::Down, &["down"]),
+    (Action::PageUp, &["pageup"]),
+    (Action::PageDown, &["pagedown"]),
+    (Action::Top, &["home"]),
+    (Action::Bottom, &["end"]),
+    (Action::ToggleStar, &["delete", "f"]),
+];
+
+const ALERTS: Table = &[
+    (Action::Back, &["esc"]),
+    (Action::NextView, &["tab"]),
+    (Action::NewAlert, &["a"]),
+    (Action::EditAlert, &["enter", "e"]),
+    (Action::DeleteAlert, &["delete", "d"]),
+    (Action::Up, &["up"]),
+    (Action::Down, &["down"]),
+    (Action::Top, &["home"]),
+    (Action::Bottom, &["end"]),
+];
+
+const CRAFTS: Table = &[
+    (Action::Back, &["esc"]),
+    (Action::NextView, &["tab"]),
+    (Action::Open, &["enter"]),
+    (Action::Up, &["up"]),
+    (Action::Down, &["down"]),
+    (Action::PageUp, &["pageup"]),
+    (Action::PageDown, &["pagedown"]),
+    (Action::Top, &["home"]),
+    (Action::Bottom, &["end"]),
+    (Action::CycleSort, &["s"]),
+    (Action::InputPricing, &["i"]),
+    (Action::OutputPricing, &["o"]),
+];
+
+const NPC: Table = &[
+    (Action::Back, &["esc"]),
+    (Action::NextView, &["tab"]),
+    (Action::Open, &["enter"]),
+    (Action::Up, &["up"]),
+    (Action::Down, &["down"]),
+    (Action::PageUp, &["pageup"]),
+    (Action::PageDown, &["pagedown"]),
+    (Action::Top, &["home"]),
+    (Action::Bottom, &["end"]),
+];
+
+const COMPARE: Table = &[
+    (Action::Back, &["esc"]),
+    (Action::Open, &["enter"]),
+    (Action::Left, &["left"]),
+    (Action::Right, &["right"]),
+    (Action::ToggleMark, &["delete", "x"]),
+    (Action::Refresh, &["r"]),
+];
+
+/// Vim keys go first so hints show them.
+const VIM_LISTS: Table = &[
+    (Action::Up, &["k", "up"]),
+    (Action::Down, &["j", "down"]),
+    (Action::PageUp, &["ctrl+u", "pageup"]),
+    (Action::PageDown, &["ctrl+d", "pagedown"]),
+    (Action::Top, &["gg", "home"]),
+    (Action::Bottom, &["G", "end"]),
+];
+
+const VIM_SEARCH: Table = &[
+    (Action::Up, &["k", "up"]),
+    (Action::Down, &["j", "down"]),
+    (Action::PageUp, &["ctrl+u", "pageup"]),
+    (Action::PageDown, &["ctrl+d", "pagedown"]),
+    (Action::Top, &["gg", "home", "ctrl+up"]),
+    (Action::Bottom, &["G", "end", "ctrl+down"]),
+    (Action::FocusSearch, &["/"]),
+    (Action::PrevCategory, &["H", "ctrl+left"]),
+    (Action::NextCategory, &["L", "ctrl+right"]),
+];
+
+const VIM_COMPARE: Table = &[
+    (Action::Left, &["h", "left"]),
+    (Action::Right, &["l", "right"]),
+];
+
+// `g` is the midline toggle here, so there is no `gg`.
+const VIM_DETAIL: Table = &[
+    (Action::Up, &["k", "up"]),
+    (Action::Down, &["j", "down"]),
+    (Action::PanLeft, &["h", "left"]),
+    (Action::PanRight, &["l", "right"]),
+    (Action::PageUp, &["ctrl+u", "pageup"]),
+    (Action::PageDown, &["ctrl+d", "pagedown"]),
+];
+
+#[derive(Debug, Clone)]
+struct Binding {
+    context: Context,
+    keys: Vec<Key>,
+    action: Action,
+}
+
+/// What a key press turned out to mean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolved {
+    Action(Action),
+    /// The start of a longer sequence; wait for the next key.
+    Pending,
+    Unbound,
+}
+
+/// The active bindings, plus any half-typed sequence.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    pending: Vec<Key>,
+    /// Keys of a sequence the last press broke off.
+    broken: Vec<Key>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&KeysConfig::default()).expect("built-in bindings parse")
+    }
+}
+
+impl Keymap {
+    pub fn from_config(config: &KeysConfig) -> Result<Self, String> {
+        let mut tables: Vec<(Context, BTreeMap<Action, Vec<String>>)> = vec![
+            (Context::Global, owned(GLOBAL)),
+            (Context::Search, owned(SEARCH)),
+            (Context::Detail, owned(DETAIL)),
+            (Context::Watchlist, owned(WATCHLIST)),
+            (Context::Alerts, owned(ALERTS)),
+            (Context::Crafts, owned(CRAFTS)),
+            (Context::Npc, owned(NPC)),
+            (Context::Compare, owned(COMPARE)),
+        ];
+        if config.preset == Preset::Vim {
+            for (context, table) in &mut tables {
+                let extra = match context {
+                    Context::Global => continue,
+                    Context::Search => VIM_SEARCH,
+                    Context::Detail => VIM_DETAIL,
+                    Context::Watchlist | Context::Alerts | Context::Crafts | Context::Npc => {
+                        VIM_LISTS
+                    }
+                    Context::Compare => VIM_COMPARE,
+                };
+                table.extend(owned(extra));
+            }
+        }
+        for (context, table) in &mut tables {
+            let user = match context {
+                Context::Global => &config.global,
+                Context::Search => &config.search,
+                Context::Detail => &config.detail,
+                Context::Watchlist => &config.watchlist,
+                Context::Alerts => &config.alerts,
+                Context::Crafts => &config.crafts,
+                Context::Npc => &config.npc,
+                Context::Compare => &config.compare,
+            };
+            table.extend(user.iter().map(|(a, keys)| (*a, keys.clone())));
+        }
+
+        let mut bindings = Vec::new();
+        for (context, table) in tables {
+            for (action, keys) in table {
+                for s in keys {
+                    let keys = parse_sequence(&s).ok_or_else(|| format!("unknown key {s:?}"))?;
+                    bindings.push(Binding {
+                        context,
+                        keys,
+                        action,
+                    });
+                }
+            }
+        }
+        check_clashes(&bindings)?;
+        Ok(Self {
+            bindings,
+            pending: Vec::new(),
+            broken: Vec::new(),
+        })
+    }
+
+    /// Feeds one key press. `text_input` means a text field has focus, so
+    /// plain characters are left for it.
+    pub fn resolve(&mut self, context: Context, key: Key, text_input: bool) -> Resolved {
+        self.broken.clear();
+        self.pending.push(key);
+        match self.lookup(context, text_input) {
+            Resolved::Unbound if self.pending.len() > 1 => {
+                // A broken-off sequence: the keys before this one go to
+                // `take_broken`, and this one may still mean something.
+                self.pending.pop();
+                let broken = std::mem::take(&mut self.pending);
+                let found = self.resolve(context, key, text_input);
+                self.broken.splice(0..0, broken);
+                found
+            }
+            Resolved::Pending => Resolved::Pending,
+            found => {
+                self.pending.clear();
+                found
+            }
+        }
+    }
+
+    /// Keys the last [`resolve`](Self::resolve) dropped from a sequence it
+    /// broke off, so the search box can type them after all.
+    pub fn take_broken(&mut self) -> Vec<Key> {
+        std::mem::take(&mut self.broken)
+    }
+
+    fn lookup(&self, context: Context, text_input: bool) -> Resolved {
+        // Help keys are kept back from text fields, so `?` opens help
+        // while typing too.
+        let usable = |b: &&Binding| {
+            (b.context == context || b.context == Context::Global)
+                && (b.action == Action::Help
+                    || !(text_input && b.keys.first().is_some_and(Key::is_plain_char)))
+        };
+        // The view's own bindings shadow global ones on the same keys.
+        let exact = self
+            .bindings
+            .iter()
+            .filter(usable)
+            .filter(|b| b.keys == self.pending)
+            .min_by_key(|b| b.context == Context::Global);
+        if let Some(b) = exact {
+            return Resolved::Action(b.action);
+        }
+        let prefix = self
+            .bindings
+            .iter()
+            .filter(usable)
+            .any(|b| b.keys.len() > self.pending.len() && b.keys.starts_with(&self.pending));
+        if prefix {
+            Resolved::Pending
+        } else {
+            Resolved::Unbound
+        }
+    }
+
+    /// Whether `key` alone triggers `action` in `context`.
+    pub fn is_bound(&self, context: Context, key: Key, action: Action) -> bool {
+        self.bindings.iter().any(|b| {
+            (b.context == context || b.context == Context::Global)
+                && b.action == action
+                && b.keys == [key]
+        })
+    }
+
+    /// The first key bound to `action`, as shown in hints: `Ctrl+S`, `gg`.
+    pub fn label(&self, context: Context, action: Action) -> Option<String> {
+        self.first_label(context, action, false)
+    }
+
+    /// Like [`label`](Self::label), but skipping keys a text field keeps.
+    pub fn text_label(&self, context: Context, action: Action) -> Option<String> {
+        self.first_label(context, action, true)
+    }
+
+    fn first_label(&self, context: Context, action: Action, text_input: bool) -> Option<String> {
+        let usable = |b: &&Binding| {
+            b.action == action && !(text_input && b.keys.first().is_some_and(Key::is_plain_char))
+        };
+        let b = self
+            .bindings
+            .iter()
+            .filter(usable)
+            .find(|b| b.context == context)
+            .or_else(|| {
+                self.bindings
+                    .iter()
+                    .filter(usable)
+                    .find(|b| b.context == Context::Global)
+            })?;
+        Some(b.keys.iter().map(Key::to_string).collect())
+    }
+
+    /// Every action reachable in `context` with all its keys, for the help
+    /// overlay. Global keys the view has taken over are left out.
+    pub fn entries(&self, context: Context) -> Vec<(Action, Vec<String>)> {
+        let mut out: BTreeMap<Action, Vec<String>> = BTreeMap::new();
+        let local = self.bindings.iter().filter(|b| b.context == context);
+        let global = self.bindings.iter().filter(|b| {
+            b.context == Context::Global
+                && !self
+                    .bindings
+                    .iter()
+                    .any(|l| l.context == context && l.keys == b.keys)
+        });
+        for b in local.chain(global) {
+            out.entry(b.action)
+                .or_default()
+                .push(b.keys.iter().map(Key::to_string).collect());
+        }
+        out.into_iter().collect()
+    }
+
+    /// `"↑/↓ navigate"`-style hint, or `None` when nothing is bound.
+    pub fn hint(&self, context: Context, actions: &[Action], text: &str) -> Option<String> {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|a| self.label(context, *a))
+            .collect();
+        (!keys.is_empty()).then(|| format!("{} {text}", keys.join("/")))
+    }
+}
+
+/// Two actions on the same keys in one view, or one on a prefix of the
+/// other's sequence, would leave one of them unreachable.
+fn check_clashes(bindings: &[Binding]) -> Result<(), String> {
+    let label = |keys: &[Key]| keys.iter().map(Key::to_string).collect::<String>();
+    for (i, a) in bindings.iter().enumerate() {
+        for b in &bindings[i + 1..] {
+            if a.context != b.context || a.action == b.action {
+                continue;
+            }
+            let context = a.context.table();
+            if a.keys == b.keys {
+                return Err(format!(
+                    "{} and {} are both bound to {:?} in {context}",
+                    a.action,
+                    b.action,
+                    label(&a.keys)
+                ));
+            }
+            let (short, long) = if a.keys.len() < b.keys.len() {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            if long.keys.starts_with(&short.keys) {
+                return Err(format!(
+                    "{} on {:?} hides {} on {:?} in {context}",
+                    short.action,
+                    label(&short.keys),
+                    long.action,
+                    label(&long.keys)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn owned(table: Table) -> BTreeMap<Action, Vec<String>> {
+    table
+        .iter()
+        .map(|(a, keys)| (*a, keys.iter().map(|k| k.to_string()).collect()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(c: char) -> Key {
+        Key::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn vim() -> Keymap {
+        Keymap::from_config(&KeysConfig {
+            preset: Preset::Vim,
+            ..KeysConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn sequences_wait_for_their_second_key() {
+        let mut keys = vim();
+
+        assert_eq!(
+            keys.resolve(Context::Watchlist, press('g'), false),
+            Resolved::Pending
+        );
+        assert_eq!(
+            keys.resolve(Context::Watchlist, press('g'), false),
+            Resolved::Action(Action::Top)
+        );
+        assert_eq!(
+            keys.resolve(Context::Watchlist, press('g'), false),
+            Resolved::Pending
+        );
+        assert_eq!(
+            keys.resolve(Context::Watchlist, press('j'), false),
+            Resolved::Action(Action::Down),
+            "a broken sequence still honours its last key"
+        );
+        assert_eq!(keys.take_broken(), [press('g')]);
+
+        assert_eq!(
+            keys.resolve(Context::Search, press('g'), false),
+            Resolved::Pending
+        );
+        assert_eq!(
+            keys.resolve(Context::Search, press('r'), false),
+            Resolved::Unbound
+        );
+        assert_eq!(
+            keys.take_broken(),
+            [press('g')],
+            "left for the search box to type"
+        );
+        assert!(keys.take_broken().is_empty());
+    }
+
+    #[test]
+    fn text_fields_keep_plain_letters() {
+        let mut keys = vim();
+        let ctrl_s = Key::parse("ctrl+s").unwrap();
+
+        assert_eq!(
+            keys.resolve(Context::Search, press('j'), true),
+            Resolved::Unbound
+        );
+        assert_eq!(
+            keys.resolve(Context::Search, press('j'), false),
+            Resolved::Action(Action::Down)
+        );
+        assert_eq!(
+            keys.resolve(Context::Search, ctrl_s, true),
+            Resolved::Action(Action::CycleSort)
+        );
+        assert_eq!(
+            keys.resolve(Context::Search, press('?'), true),
+            Resolved::Action(Action::Help)
+        );
+        assert_eq!(
+            keys.label(Context::Search, Action::Down).as_deref(),
+            Some("j")
+        );
+        assert_eq!(
+            keys.text_label(Context::Search, Action::Down).as_deref(),
+            Some("↓")
+        );
+    }
+
+    #[test]
+    fn rebinding_an_action_replaces_its_keys() {
+        let mut config = KeysConfig::default();
+        config.detail.insert(Action::ToggleSma, vec!["v".into()]);
+        let mut keys = Keymap::from_config(&config).unwrap();
+
+        assert_eq!(
+            keys.resolve(Context::Detail, press('v'), false),
+            Resolved::Action(Action::ToggleSma)
+        );
+        assert_eq!(
+            keys.resolve(Context::Detail, press('m'), false),
+            Resolved::Unbound
+        );
+        assert_eq!(
+            keys.label(Context::Detail, Action::ToggleSma).as_deref(),
+            Some("v")
+        );
+    }
+
+    #[test]
+    fn two_actions_on_one_key_are_rejected() {
+        let mut config = KeysConfig::default();
+        config.detail.insert(Action::ToggleSma, vec!["s".into()]);
+        let err = Keymap::from_config(&config).unwrap_err();
+        assert!(
+            err.contains("toggle_sma") && err.contains("toggle_spread"),
+            "{err}"
+        );
+
+        let mut config = KeysConfig::default();
+        config.detail.insert(Action::Top, vec!["gg".into()]);
+        let err = Keymap::from_config(&config).unwrap_err();
+        assert!(
+            err.contains("toggle_midline") && err.contains("top"),
+            "{err}"
+        );
+
+        let mut config = KeysConfig::default();
+        config.search.insert(Action::Quit, vec!["ctrl+s".into()]);
+        assert!(Keymap::from_config(&config).is_err());
+        config.search.clear();
+        config.global.insert(Action::Quit, vec!["ctrl+s".into()]);
+        assert!(
+            Keymap::from_config(&config).is_ok(),
+            "views may shadow global keys"
+        );
+    }
+
+    #[test]
+    fn help_lists_view_and_global_actions_once() {
+        let keys = Keymap::default();
+
+        let entries = keys.entries(Context::Detail);
+
+        let sma = entries
+            .iter()
+            .find(|(a, _)| *a == Action::ToggleSma)
+            .unwrap();
+        assert_eq!(sma.1, ["m"]);
+        assert!(entries.iter().any(|(a, _)| *a == Action::Quit));
+        assert!(
+            !entries.iter().any(|(a, _)| *a == Action::CycleSort),
+            "search-only"
+        );
+    }
+
+    #[test]
+    fn keys_parse_and_print_the_way_hints_show_them() {
+        let label = |s: &str| {
+            parse_sequence(s)
+                .unwrap()
+                .iter()
+                .map(Key::to_string)
+                .collect::<String>()
+        };
+
+        assert_eq!(label("ctrl+s"), "Ctrl+S");
+        assert_eq!(label("G"), "G");
+        assert_eq!(label("shift+g"), "G");
+        assert_eq!(label("gg"), "gg");
+        assert_eq!(label("pagedown"), "PgDn");
+        assert_eq!(label("f5"), "F5");
+        assert!(parse_sequence("hyper+x").is_none());
+    }
+}
@@ -1,2 +1,18 @@
+pub mod alerts;
+pub mod book;
+pub mod categories;
+pub mod chart;
+pub mod columns;
+pub mod config;
+pub mod crafts;
+pub mod history;
+pub mod indicators;
+pub mod items;
+pub mod keymap;
+pub mod npc;
+pub mod overlays;
+pub mod query;
 pub mod search;
+pub mod source;
 pub mod state;
+pub mod watchlist;
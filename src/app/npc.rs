@@ -0,0 +1,121 @@
+use crate::app::state::prices;
+use hypixel::models::skyblock::BazaarProduct;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static NPC_PRICES: LazyLock<HashMap<String, f64>> = LazyLock::new(|| {
+    toml::from_str(include_str!("../../assets/npc_prices.toml"))
+        .expect("bundled npc_prices.toml is valid")
+});
+
+/// What a merchant pays for one `id`, if the bundled table knows it.
+pub fn npc_price(id: &str) -> Option<f64> {
+    NPC_PRICES.get(id).copied()
+}
+
+/// A product the bazaar sells for less than a merchant buys it back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NpcFlip {
+    pub product_id: String,
+    pub npc_price: f64,
+    pub instant_buy: f64,
+    /// Top bid; a buy order placed there fills below the NPC price.
+    pub buy_order: f64,
+    /// Units listed in `buy_summary` below the NPC price.
+    pub available: i64,
+    /// Profit from buying all of `available` and selling it to the NPC.
+    pub attainable: f64,
+}
+
+impl NpcFlip {
+    pub fn instant_profit(&self) -> f64 {
+        self.npc_price - self.instant_buy
+    }
+
+    pub fn order_profit(&self) -> f64 {
+        self.npc_price - self.buy_order
+    }
+}
+
+/// Walks the asks of `product` for levels under its NPC price. `None` when
+/// neither an instant buy nor a buy order comes in below it.
+pub fn npc_flip(product: &BazaarProduct) -> Option<NpcFlip> {
+    let npc_price = npc_price(&product.product_id)?;
+    let px = prices(product)?;
+    if px.instant_buy >= npc_price && px.instant_sell >= npc_price {
+        return None;
+    }
+    let (available, attainable) = product
+        .buy_summary
+        .iter()
+        .take_while(|o| o.price_per_unit < npc_price)
+        .fold((0, 0.0), |(n, profit), o| {
+            (
+                n + o.amount,
+                profit + (npc_price - o.price_per_unit) * o.amount as f64,
+            )
+        });
+    Some(NpcFlip {
+        product_id: product.product_id.clone(),
+        npc_price,
+        instant_buy: px.instant_buy,
+        buy_order: px.instant_sell,
+        available,
+        attainable,
+    })
+}
+
+/// Every NPC flip, most attainable profit first; order-only flips follow,
+/// best margin per item first.
+pub fn ranked(products: &IndexMap<String, BazaarProduct>) -> Vec<NpcFlip> {
+    let mut flips: Vec<NpcFlip> = products.values().filter_map(npc_flip).collect();
+    flips.sort_by(|a, b| {
+        b.attainable
+            .total_cmp(&a.attainable)
+            .then(b.order_profit().total_cmp(&a.order_profit()))
+    });
+    flips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hypixel::models::skyblock::BazaarOrder;
+
+    fn order(price: f64, amount: i64) -> BazaarOrder {
+        BazaarOrder {
+            amount,
+            price_per_unit: price,
+            orders: 1,
+        }
+    }
+
+    #[test]
+    fn only_asks_under_the_npc_price_count() {
+        let npc = npc_price("ENCHANTED_DIAMOND").unwrap();
+        let product = BazaarProduct {
+            product_id: "ENCHANTED_DIAMOND".into(),
+            buy_summary: vec![
+                order(npc - 10.0, 5),
+                order(npc - 1.0, 20),
+                order(npc + 5.0, 100),
+            ],
+            sell_summary: vec![order(npc - 50.0, 1)],
+            quick_status: None,
+        };
+
+        let flip = npc_flip(&product).unwrap();
+        assert_eq!(flip.available, 25);
+        assert_eq!(flip.attainable, 10.0 * 5.0 + 20.0);
+        assert_eq!(flip.instant_profit(), 10.0);
+        assert_eq!(flip.order_profit(), 50.0);
+
+        let pricey = BazaarProduct {
+            buy_summary: vec![order(npc + 5.0, 100)],
+            sell_summary: vec![order(npc + 1.0, 1)],
+            ..product
+        };
+        assert_eq!(npc_flip(&pricey), None);
+    }
+}
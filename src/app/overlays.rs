@@ -0,0 +1,149 @@
+/// `(x, value)` points, as the history chart plots them.
+pub type Series = Vec<(f64, f64)>;
+
+/// Mean of each run of `k` values, from the `k`th point on.
+pub fn sma(pts: &[(f64, f64)], k: usize) -> Series {
+    if k == 0 || pts.len() < k {
+        return Vec::new();
+    }
+    let mut out = Vec::with_capacity(pts.len() - k + 1);
+    let mut sum = 0.0;
+    for (i, p) in pts.iter().enumerate() {
+        sum += p.1;
+        if i >= k {
+            sum -= pts[i - k].1;
+        }
+        if i + 1 >= k {
+            out.push((p.0, sum / k as f64));
+        }
+    }
+    out
+}
+
+/// Exponential average with the usual `2 / (k + 1)` smoothing, seeded with
+/// the mean of the first `k` values.
+pub fn ema(pts: &[(f64, f64)], k: usize) -> Series {
+    if k == 0 || pts.len() < k {
+        return Vec::new();
+    }
+    let alpha = 2.0 / (k as f64 + 1.0);
+    let mut value = pts[..k].iter().map(|p| p.1).sum::<f64>() / k as f64;
+    let mut out = Vec::with_capacity(pts.len() - k + 1);
+    out.push((pts[k - 1].0, value));
+    for p in &pts[k..] {
+        value += alpha * (p.1 - value);
+        out.push((p.0, value));
+    }
+    out
+}
+
+/// Upper and lower Bollinger bands: the `k`-point mean plus and minus
+/// `width` standard deviations.
+pub fn bollinger(pts: &[(f64, f64)], k: usize, width: f64) -> (Series, Series) {
+    if k == 0 || pts.len() < k {
+        return (Vec::new(), Vec::new());
+    }
+    let mut upper = Vec::with_capacity(pts.len() - k + 1);
+    let mut lower = Vec::with_capacity(pts.len() - k + 1);
+    for (i, window) in pts.windows(k).enumerate() {
+        let mean = window.iter().map(|p| p.1).sum::<f64>() / k as f64;
+        let var = window.iter().map(|p| (p.1 - mean).powi(2)).sum::<f64>() / k as f64;
+        let x = pts[i + k - 1].0;
+        upper.push((x, mean + width * var.sqrt()));
+        lower.push((x, mean - width * var.sqrt()));
+    }
+    (upper, lower)
+}
+
+/// Time-weighted average over the last `k` points: each value counts for
+/// as long as it stood before the next one. Samples carry no traded volume,
+/// and the history log only writes when a price moves, so time on the book
+/// is the closest weight there is.
+pub fn twap(pts: &[(f64, f64)], k: usize) -> Series {
+    if k == 0 || pts.len() < k {
+        return Vec::new();
+    }
+    pts.windows(k)
+        .map(|window| {
+            let (first, last) = (window[0], window[k - 1]);
+            let span = last.0 - first.0;
+            if span <= 0.0 {
+                return last;
+            }
+            let area: f64 = window.windows(2).map(|w| w[0].1 * (w[1].0 - w[0].0)).sum();
+            (last.0, area / span)
+        })
+        .collect()
+}
+
+/// Highest and lowest value of the last `k` points.
+pub fn envelope(pts: &[(f64, f64)], k: usize) -> (Series, Series) {
+    if k == 0 || pts.len() < k {
+        return (Vec::new(), Vec::new());
+    }
+    pts.windows(k)
+        .map(|window| {
+            let x = window[k - 1].0;
+            let hi = window.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+            let lo = window.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+            ((x, hi), (x, lo))
+        })
+        .unzip()
+}
+
+/// Where the latest point of a series sits among the rest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesStats {
+    pub current: f64,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Share of points at or below `current`, in percent.
+    pub percentile: f64,
+}
+
+pub fn stats(pts: &[(f64, f64)]) -> Option<SeriesStats> {
+    let current = pts.last()?.1;
+    let n = pts.len() as f64;
+    let values = || pts.iter().map(|p| p.1);
+    Some(SeriesStats {
+        current,
+        mean: values().sum::<f64>() / n,
+        min: values().fold(f64::INFINITY, f64::min),
+        max: values().fold(f64::NEG_INFINITY, f64::max),
+        percentile: values().filter(|&v| v <= current).count() as f64 / n * 100.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_start_once_a_window_fills() {
+        let pts = [(0.0, 10.0), (1.0, 20.0), (3.0, 30.0), (4.0, 10.0)];
+
+        assert_eq!(sma(&pts, 2), [(1.0, 15.0), (3.0, 25.0), (4.0, 20.0)]);
+        // Seeded at 15, then two thirds of the way to each new value.
+        assert_eq!(ema(&pts, 2), [(1.0, 15.0), (3.0, 25.0), (4.0, 15.0)]);
+        // 20 stood for two seconds and 30 for one.
+        assert_eq!(twap(&pts, 3), [(3.0, 50.0 / 3.0), (4.0, 70.0 / 3.0)]);
+
+        let (hi, lo) = envelope(&pts, 3);
+        assert_eq!(hi, [(3.0, 30.0), (4.0, 30.0)]);
+        assert_eq!(lo, [(3.0, 10.0), (4.0, 10.0)]);
+
+        let (upper, lower) = bollinger(&pts, 2, 2.0);
+        assert_eq!((upper[0], lower[0]), ((1.0, 25.0), (1.0, 5.0)));
+        assert!(sma(&pts, 5).is_empty() && bollinger(&pts, 5, 2.0).0.is_empty());
+    }
+
+    #[test]
+    fn a_narrow_latest_spread_ranks_low() {
+        let pts = [(0.0, 4.0), (1.0, 8.0), (2.0, 6.0), (3.0, 2.0)];
+        let s = stats(&pts).unwrap();
+        assert_eq!((s.current, s.mean, s.min, s.max), (2.0, 5.0, 2.0, 8.0));
+        assert_eq!(s.percentile, 25.0);
+        assert_eq!(stats(&[]), None);
+    }
+}
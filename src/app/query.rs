@@ -0,0 +1,236 @@
+use crate::app::categories;
+use crate::app::columns::Column;
+use crate::app::search::rank;
+use crate::app::state::BazaarData;
+use hypixel::models::skyblock::BazaarProduct;
+use hypixel::util::market::BazaarFlip;
+
+/// Field names accepted before an operator, and the column each reads.
+const FIELDS: &[(&str, Column)] = &[
+    ("buy", Column::Buy),
+    ("sell", Column::Sell),
+    ("spread", Column::Spread),
+    ("spread%", Column::SpreadPct),
+    ("spreadpct", Column::SpreadPct),
+    ("profit", Column::Profit),
+    ("margin", Column::Margin),
+    ("vol", Column::Volume),
+    ("volume", Column::Volume),
+    ("imbal", Column::Imbalance),
+    ("imbalance", Column::Imbalance),
+    ("flow", Column::Flow),
+    ("bidsize", Column::AvgBid),
+    ("asksize", Column::AvgAsk),
+    ("depth", Column::Depth),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Cmp::Lt => lhs < rhs,
+            Cmp::Le => lhs <= rhs,
+            Cmp::Gt => lhs > rhs,
+            Cmp::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// One `field<op>value` term, e.g. `margin>5%`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Predicate {
+    pub column: Column,
+    pub cmp: Cmp,
+    pub value: f64,
+}
+
+/// A search box input split into the fuzzy name part and numeric filters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub name: String,
+    pub predicates: Vec<Predicate>,
+    /// From a `cat:` term.
+    pub category: Option<&'static str>,
+    /// The first term that failed to parse; it is left out of the filter.
+    pub error: Option<String>,
+}
+
+impl Query {
+    /// Words without an operator make up the name; the rest are predicates
+    /// and an optional `cat:` category.
+    pub fn parse(input: &str) -> Self {
+        let mut query = Query::default();
+        let mut words = Vec::new();
+        for token in input.split_whitespace() {
+            if let Some(name) = token.strip_prefix("cat:") {
+                match categories::lookup(name) {
+                    Some(c) if !name.is_empty() => query.category = Some(c),
+                    _ => {
+                        query.error.get_or_insert(format!("no category `{name}`"));
+                    }
+                }
+                continue;
+            }
+            if !token.contains(['<', '>', '=']) {
+                words.push(token);
+                continue;
+            }
+            match parse_predicate(token) {
+                Ok(p) => query.predicates.push(p),
+                Err(e) => {
+                    query.error.get_or_insert(e);
+                }
+            }
+        }
+        query.name = words.join(" ");
+        query
+    }
+
+    /// Products with no value for a filtered field never match it.
+    pub fn matches(&self, product: &BazaarProduct, flip: Option<&BazaarFlip>) -> bool {
+        self.predicates.iter().all(|p| {
+            p.column
+                .value(product, flip)
+                .is_some_and(|v| p.cmp.holds(v, p.value))
+        })
+    }
+
+    /// Index positions passing every predicate, best name match first; an
+    /// empty name keeps index order.
+    pub fn filter(&self, data: &BazaarData) -> Vec<usize> {
+        let mut hits = if self.name.is_empty() {
+            (0..data.index.len()).collect()
+        } else {
+            rank(&self.name, &data.index)
+        };
+        if let Some(category) = self.category {
+            hits.retain(|&i| data.index[i].category == category);
+        }
+        if !self.predicates.is_empty() {
+            hits.retain(|&i| {
+                let id = &data.index[i].id;
+                data.products
+                    .get(id)
+                    .is_some_and(|p| self.matches(p, data.flips.get(id)))
+            });
+        }
+        hits
+    }
+}
+
+fn parse_predicate(token: &str) -> Result<Predicate, String> {
+    let at = token.find(['<', '>', '=']).unwrap_or(token.len());
+    let (field, rest) = token.split_at(at);
+    let (cmp, value) = if let Some(v) = rest.strip_prefix(">=") {
+        (Cmp::Ge, v)
+    } else if let Some(v) = rest.strip_prefix("<=") {
+        (Cmp::Le, v)
+    } else if let Some(v) = rest.strip_prefix('>') {
+        (Cmp::Gt, v)
+    } else if let Some(v) = rest.strip_prefix('<') {
+        (Cmp::Lt, v)
+    } else {
+        return Err(format!("`{token}`: compare with >, >=, < or <="));
+    };
+
+    if field.is_empty() {
+        return Err(format!("`{token}` needs a field, e.g. margin{rest}"));
+    }
+    let field = field.to_ascii_lowercase();
+    let Some(&(_, mut column)) = FIELDS.iter().find(|(name, _)| *name == field) else {
+        return Err(format!(
+            "unknown field `{field}` (buy, sell, spread, spread%, profit, margin, vol, imbal, flow, bidsize, asksize, depth)"
+        ));
+    };
+    if value.is_empty() {
+        return Err(format!("`{token}` needs a value"));
+    }
+
+    let (number, percent) = match value.strip_suffix('%') {
+        Some(n) => (n, true),
+        None => (value, false),
+    };
+    if percent {
+        // `spread>5%` reads naturally; take it to mean the % column.
+        column = match column {
+            Column::Spread | Column::SpreadPct => Column::SpreadPct,
+            Column::Margin => Column::Margin,
+            _ => return Err(format!("`{field}` is not a percentage")),
+        };
+    }
+    let value = parse_number(number).ok_or_else(|| format!("`{value}` is not a number"))?;
+    Ok(Predicate { column, cmp, value })
+}
+
+/// `1500`, `1,500`, `1.5k`, `2m` or `1b`.
+fn parse_number(s: &str) -> Option<f64> {
+    let s: String = s.chars().filter(|c| !matches!(c, ',' | '_')).collect();
+    let (digits, scale) = match s.chars().last()? {
+        'k' | 'K' => (&s[..s.len() - 1], 1e3),
+        'm' | 'M' => (&s[..s.len() - 1], 1e6),
+        'b' | 'B' => (&s[..s.len() - 1], 1e9),
+        _ => (s.as_str(), 1.0),
+    };
+    let n = digits.parse::<f64>().ok()? * scale;
+    n.is_finite().then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_and_predicates_are_split_apart() {
+        let q = Query::parse("ench margin>5% vol>=100k Buy<10,000 diamond");
+
+        assert_eq!(q.name, "ench diamond");
+        assert_eq!(q.error, None);
+        assert_eq!(
+            q.predicates,
+            [
+                Predicate {
+                    column: Column::Margin,
+                    cmp: Cmp::Gt,
+                    value: 5.0
+                },
+                Predicate {
+                    column: Column::Volume,
+                    cmp: Cmp::Ge,
+                    value: 100_000.0
+                },
+                Predicate {
+                    column: Column::Buy,
+                    cmp: Cmp::Lt,
+                    value: 10_000.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_terms_are_reported_and_left_out() {
+        let q = Query::parse("ench mragin>5 profit> spread>2% cat:farm");
+
+        assert_eq!(q.name, "ench");
+        assert_eq!(q.category, Some("Farming"));
+        assert!(q.error.unwrap().contains("mragin"));
+        assert_eq!(
+            q.predicates,
+            [Predicate {
+                column: Column::SpreadPct,
+                cmp: Cmp::Gt,
+                value: 2.0
+            }]
+        );
+        assert!(Query::parse("buy>5%").error.is_some());
+        assert!(Query::parse("buy=5").error.is_some());
+        assert!(Query::parse("cat:").error.is_some());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::app::state::ProductIndexItem;
+use crate::util::normalize;
+
 pub const MIN_SCORE: i32 = i32::MIN / 2;
 
 /// Fuzzy score assuming `query_norm` and `candidate_norm` are already normalized via `normalize`.
@@ -53,6 +56,28 @@ pub fn score_normalized(query_norm: &str, candidate_norm: &str) -> i32 {
     score
 }
 
+/// Index positions matching `query` by name or alias, best score first,
+/// ties in index order.
+pub fn rank(query: &str, index: &[ProductIndexItem]) -> Vec<usize> {
+    let query = normalize(query);
+    let mut scored: Vec<(usize, i32)> = index
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let best = std::iter::once(&item.norm_display)
+                .chain(&item.norm_aliases)
+                .map(|candidate| score_normalized(&query, candidate))
+                .max()
+                .unwrap_or(MIN_SCORE);
+            (i, best)
+        })
+        .filter(|(_, score)| *score > MIN_SCORE)
+        .collect();
+
+    scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
 fn is_subsequence(needle: &str, hay: &str) -> bool {
     let mut it = hay.chars();
     for ch in needle.chars() {
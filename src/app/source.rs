@@ -0,0 +1,277 @@
+use hypixel::HypixelClient;
+use hypixel::models::skyblock::Bazaar;
+use std::fmt;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum SourceError {
+    Api(hypixel::Error),
+    Io(io::Error),
+    Decode(serde_json::Error),
+    /// A replay or script has handed out its last snapshot.
+    Exhausted,
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Decode(e) => write!(f, "bad snapshot file: {e}"),
+            Self::Exhausted => write!(f, "replay finished"),
+        }
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+impl From<hypixel::Error> for SourceError {
+    fn from(e: hypixel::Error) -> Self {
+        Self::Api(e)
+    }
+}
+
+impl From<io::Error> for SourceError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SourceError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Decode(e)
+    }
+}
+
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<Bazaar, SourceError>> + Send + 'a>>;
+
+/// Anything that can produce a bazaar snapshot on demand.
+///
+/// Boxed futures rather than `async fn` so `App` can hold any source behind
+/// one `Arc<dyn BazaarSource>`.
+pub trait BazaarSource: fmt::Debug + Send + Sync {
+    fn fetch(&self) -> FetchFuture<'_>;
+
+    /// The same source with a new request timeout, or `None` when it makes
+    /// no requests.
+    fn with_timeout(&self, _timeout: Duration) -> Option<Arc<dyn BazaarSource>> {
+        None
+    }
+}
+
+/// The live API client.
+pub fn live_client(timeout: Duration) -> HypixelClient {
+    // Keyless endpoint, so no API key is needed.
+    HypixelClient::builder()
+        .timeout(timeout)
+        .retry_on_rate_limit(2)
+        .build()
+}
+
+impl BazaarSource for HypixelClient {
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move { Ok(self.skyblock_bazaar().await?) })
+    }
+
+    fn with_timeout(&self, timeout: Duration) -> Option<Arc<dyn BazaarSource>> {
+        Some(Arc::new(live_client(timeout)))
+    }
+}
+
+impl BazaarSource for Arc<dyn BazaarSource> {
+    fn fetch(&self) -> FetchFuture<'_> {
+        (**self).fetch()
+    }
+
+    fn with_timeout(&self, timeout: Duration) -> Option<Arc<dyn BazaarSource>> {
+        (**self).with_timeout(timeout)
+    }
+}
+
+/// Wraps another source and saves everything it returns.
+#[derive(Debug)]
+pub struct Recording<S> {
+    inner: S,
+    recorder: Recorder,
+}
+
+impl<S> Recording<S> {
+    pub fn new(inner: S, recorder: Recorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<S: BazaarSource> BazaarSource for Recording<S> {
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move {
+            let bazaar = self.inner.fetch().await?;
+            // Snapshots run to megabytes; keep the disk write off the async workers.
+            let recorder = self.recorder.clone();
+            tokio::task::spawn_blocking(move || recorder.write(&bazaar).map(|()| bazaar))
+                .await
+                .map_err(io::Error::other)?
+        })
+    }
+
+    fn with_timeout(&self, timeout: Duration) -> Option<Arc<dyn BazaarSource>> {
+        let inner = self.inner.with_timeout(timeout)?;
+        Some(Arc::new(Recording::new(inner, self.recorder.clone())))
+    }
+}
+
+/// Saves each snapshot as `<lastUpdated unix ms>.json`, so names sort by time
+/// and a snapshot polled twice is saved once.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn write(&self, bazaar: &Bazaar) -> Result<(), SourceError> {
+        let path = self.dir.join(format!("{:013}.json", bazaar.last_updated));
+        let file = match File::create_new(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::to_writer(BufWriter::new(file), bazaar)?;
+        Ok(())
+    }
+}
+
+/// Plays back a directory of JSON snapshots, recorded or hand-written
+/// fixtures, in file-name order.
+#[derive(Debug)]
+pub struct Replay {
+    files: Vec<PathBuf>,
+    cursor: Mutex<usize>,
+}
+
+impl Replay {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        if files.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no .json snapshots in {}", dir.display()),
+            ));
+        }
+        Ok(Self {
+            files,
+            cursor: Mutex::new(0),
+        })
+    }
+
+    fn next_snapshot(&self) -> Result<Bazaar, SourceError> {
+        let path = {
+            let mut cursor = self.cursor.lock().unwrap_or_else(|e| e.into_inner());
+            let path = self.files.get(*cursor).ok_or(SourceError::Exhausted)?;
+            *cursor += 1;
+            path.clone()
+        };
+        let file = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+impl BazaarSource for Replay {
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move { self.next_snapshot() })
+    }
+}
+
+/// Hands out prepared snapshots in order, for driving the app in tests.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct Scripted {
+    snapshots: Mutex<std::collections::VecDeque<Bazaar>>,
+}
+
+#[cfg(test)]
+impl Scripted {
+    pub fn new(snapshots: impl IntoIterator<Item = Bazaar>) -> Self {
+        Self {
+            snapshots: Mutex::new(snapshots.into_iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl BazaarSource for Scripted {
+    fn fetch(&self) -> FetchFuture<'_> {
+        Box::pin(async move {
+            self.snapshots
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .pop_front()
+                .ok_or(SourceError::Exhausted)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::Config;
+    use crate::app::state::{App, prices};
+    use hypixel::models::skyblock::{BazaarOrder, BazaarProduct};
+    use std::sync::Arc;
+
+    fn snapshot(last_updated: i64, ask: f64) -> Bazaar {
+        let order = |price| BazaarOrder {
+            amount: 1,
+            price_per_unit: price,
+            orders: 1,
+        };
+        let product = BazaarProduct {
+            product_id: "ENCHANTED_DIAMOND".into(),
+            buy_summary: vec![order(ask)],
+            sell_summary: vec![order(ask - 1.0)],
+            quick_status: None,
+        };
+        Bazaar {
+            last_updated,
+            products: [("ENCHANTED_DIAMOND".to_string(), product)].into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_returns_recorded_snapshots_in_order() {
+        let dir = std::env::temp_dir().join(format!("bazaar-tui-replay-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let recorder = Recorder::new(&dir).unwrap();
+        // The repeat poll of snapshot 2 is not saved again.
+        for bazaar in [snapshot(1, 10.0), snapshot(2, 12.0), snapshot(2, 12.0)] {
+            recorder.write(&bazaar).unwrap();
+        }
+        let source = Arc::new(Replay::open(&dir).unwrap());
+
+        let mut app = App::new(
+            source.clone(),
+            source.fetch().await.unwrap(),
+            Config::default(),
+        );
+        app.apply_snapshot(source.fetch().await.unwrap());
+
+        assert_eq!(app.data.last_updated, 2);
+        let px = prices(&app.data.products["ENCHANTED_DIAMOND"]).unwrap();
+        assert_eq!(px.instant_buy, 12.0);
+        assert!(matches!(source.fetch().await, Err(SourceError::Exhausted)));
+    }
+}
@@ -1,13 +1,31 @@
-use crate::app::search::score_normalized;
-use crate::util::{normalize, pretty_name};
-use hypixel::HypixelClient;
+use crate::app::alerts::{AlertBook, AlertForm};
+use crate::app::book::FillCalc;
+use crate::app::categories;
+use crate::app::chart::{
+    CANDLE_INTERVALS, CHART_ZOOM_STEP, ChartRange, ChartWindow, MAX_CHART_ZOOM,
+};
+use crate::app::columns::{Column, TableLayout};
+use crate::app::config::{Config, Overrides};
+use crate::app::crafts::{self, CraftQuote, CraftRank, Pricing};
+use crate::app::history::{HISTORY_CAPACITY, HistoryStore, HistoryWriter, Sample};
+use crate::app::items::{self, Rarity};
+use crate::app::keymap::{Action, Keymap};
+use crate::app::npc::{self, NpcFlip};
+use crate::app::query::Query;
+use crate::app::source::BazaarSource;
+use crate::app::watchlist::Watchlist;
+use crate::util::{fmt_time, normalize, now_ms};
 use hypixel::models::skyblock::{Bazaar, BazaarProduct};
 use hypixel::util::market::{self, BazaarFlip};
 use indexmap::IndexMap;
+use ratatui::layout::Rect;
+use std::cmp::Ordering;
 use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{Notify, mpsc, oneshot},
     task::JoinHandle,
 };
 
@@ -21,6 +39,11 @@ pub enum SearchMode {
 pub enum View {
     Search,
     Detail,
+    Watchlist,
+    Alerts,
+    Crafts,
+    Npc,
+    Compare,
 }
 
 #[derive(Debug, Clone)]
@@ -28,12 +51,13 @@ pub struct ProductIndexItem {
     pub id: String,
     pub display: String,
     pub norm_display: String,
+    /// Normalized alternative names, matched like `norm_display`.
+    pub norm_aliases: Vec<String>,
+    pub rarity: Option<Rarity>,
+    /// Bundled category, or [`categories::UNCATEGORIZED`].
+    pub category: &'static str,
 }
 
-/// Weekly movement needed on both sides before a spread is worth ranking;
-/// without it the list is topped by unfillable one-sided books.
-pub const MIN_WEEKLY_VOLUME: i64 = 1_000;
-
 #[derive(Debug)]
 pub struct BazaarData {
     pub products: IndexMap<String, BazaarProduct>,
@@ -43,6 +67,25 @@ pub struct BazaarData {
     pub flips: HashMap<String, BazaarFlip>,
 }
 
+impl BazaarData {
+    pub fn new(bazaar: Bazaar, config: &Config) -> Self {
+        let mut products = IndexMap::new();
+        for (k, v) in bazaar.products {
+            products.insert(k, v);
+        }
+
+        let flips = compute_flips(&products, config);
+        let index = build_index(&products);
+
+        Self {
+            products,
+            last_updated: bazaar.last_updated,
+            index,
+            flips,
+        }
+    }
+}
+
 /// What a player can transact at right now.
 ///
 /// `buy_summary` is the ask side and `sell_summary` the bid side, despite the
@@ -92,21 +135,129 @@ pub struct SearchState {
     pub selected_index: usize,
     pub needs_filter: bool,
     pub last_input_change: Instant,
-    pub sort_by_profit: bool,
+    /// Category tab; `None` shows every product.
+    pub category: Option<&'static str>,
+    /// Why part of the query was ignored, shown in the search box.
+    pub query_error: Option<String>,
+    /// Visible columns and sort order, shared with the watchlist.
+    pub table: TableLayout,
+    /// Column chooser cursor; `None` while it is closed.
+    pub chooser: Option<usize>,
 }
 
 #[derive(Debug)]
 pub struct DetailState {
     pub product_id: Option<String>,
-    pub history: VecDeque<(Instant, f64, f64)>, // (time, buy, sell)
+    pub history: VecDeque<Sample>,
     pub show_percent: bool,
     pub show_sma: bool,
     pub show_midline: bool,
-    
-    refresh_task: Option<JoinHandle<()>>,
-    cancel_tx: Option<oneshot::Sender<()>>,
+    pub show_ema: bool,
+    pub show_bollinger: bool,
+    pub show_twap: bool,
+    pub show_envelope: bool,
+    /// Spread history pane under the buy and sell panes.
+    pub show_spread: bool,
+    /// OHLC candles in place of the price lines.
+    pub show_candles: bool,
+    /// Index into [`CANDLE_INTERVALS`]; `None` picks one to fit the pane.
+    pub candle_interval: Option<usize>,
+    /// Full order book ladder and depth chart in place of the summary panes.
+    pub show_depth: bool,
+    /// Ladder rows scrolled from the view centred on the mid price.
+    pub depth_scroll: isize,
+    /// Open fill calculator, if any.
+    pub fill: Option<FillCalc>,
+    /// Preset span of the history chart.
+    pub range: ChartRange,
+    /// History chart zoom steps; 0 shows the whole range, each step
+    /// [`CHART_ZOOM_STEP`] of the span before it.
+    pub zoom: u32,
+    /// History chart pan steps back from the latest sample, each
+    /// [`PAN_STEP`](crate::app::chart::PAN_STEP) of the visible span.
+    pub pan: u32,
+    /// Where `exit_detail` goes back to.
+    pub return_view: View,
+}
+
+/// Screen areas the last frame drew, for mapping mouse events back onto
+/// them. Views fill the hit areas in as they render.
+#[derive(Debug, Default)]
+pub struct MouseState {
+    /// The visible rows of the focused list, and the index of the first.
+    pub list: Option<(Rect, usize)>,
+    /// Clickable header labels and what they do.
+    pub buttons: Vec<(Rect, Action)>,
+    /// Category tabs of the search view.
+    pub tabs: Vec<(Rect, Option<&'static str>)>,
+    /// Sortable column captions of the search table.
+    pub columns: Vec<(Rect, Column)>,
+    pub chart: Option<Rect>,
+    /// Row and time of the last click, for spotting double-clicks.
+    pub last_click: Option<(usize, Instant)>,
+}
+
+impl MouseState {
+    pub fn clear_areas(&mut self) {
+        self.list = None;
+        self.buttons.clear();
+        self.tabs.clear();
+        self.columns.clear();
+        self.chart = None;
+    }
 }
 
+#[derive(Debug)]
+pub struct WatchlistState {
+    pub list: Watchlist,
+    pub selected_index: usize,
+}
+
+#[derive(Debug)]
+pub struct AlertsState {
+    pub book: AlertBook,
+    pub selected_index: usize,
+    pub form: Option<AlertForm>,
+    /// The status bar flashes until then.
+    pub flash_until: Option<Instant>,
+    bell_pending: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct CraftsState {
+    /// Recipe under the cursor, by position in [`crafts::recipes`]; kept
+    /// across re-ranking so refreshes don't move it.
+    pub selected: Option<usize>,
+    pub inputs: Pricing,
+    pub output: Pricing,
+    pub rank: CraftRank,
+    /// Row in the selected recipe's breakdown while drilling into it.
+    pub part: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct NpcState {
+    pub selected_index: usize,
+}
+
+/// Products marked in the search list, shown side by side.
+#[derive(Debug, Default)]
+pub struct CompareState {
+    /// In the order they were marked; at most [`MAX_COMPARE`].
+    pub marked: Vec<String>,
+    /// The product column the cursor is on.
+    pub selected: usize,
+    /// Sample history per marked product, in the same order, while the
+    /// view is open.
+    pub history: Vec<VecDeque<Sample>>,
+}
+
+/// Products the compare view fits side by side.
+pub const MAX_COMPARE: usize = 4;
+
+/// How long the status bar flashes after an alert fires.
+pub const ALERT_FLASH: Duration = Duration::from_secs(5);
+
 #[derive(Debug)]
 pub struct App {
     pub view: View,
@@ -114,45 +265,128 @@ pub struct App {
     pub data: BazaarData,
     pub search: SearchState,
     pub detail: DetailState,
-    pub update_tx: Option<mpsc::UnboundedSender<BazaarProduct>>,
-    client: HypixelClient,
+    pub watch: WatchlistState,
+    pub alerts: AlertsState,
+    pub crafts: CraftsState,
+    pub npc: NpcState,
+    pub compare: CompareState,
+    pub update_tx: Option<mpsc::UnboundedSender<Update>>,
+    pub config: Config,
+    pub keymap: Keymap,
+    /// Help overlay scroll offset; `None` while it is closed.
+    pub help: Option<usize>,
+    pub mouse: MouseState,
+    /// Reapplied over the file on every reload.
+    overrides: Overrides,
+    source: Arc<dyn BazaarSource>,
+    history_store: Option<HistoryStore>,
+    refresh_task: Option<JoinHandle<()>>,
+    cancel_tx: Option<oneshot::Sender<()>>,
+    /// Wakes the poller for an early tick, so manual refreshes are logged too.
+    refresh_now: Arc<Notify>,
 }
 
-impl App {
-    pub fn new(client: HypixelClient, bazaar: Bazaar) -> Self {
-        let flips: HashMap<String, BazaarFlip> = market::bazaar_flips(&bazaar, MIN_WEEKLY_VOLUME)
-            .into_iter()
-            .map(|f| (f.product_id.clone(), f))
-            .collect();
+/// What background tasks hand back to the event loop.
+#[derive(Debug)]
+pub enum Update {
+    Snapshot(Bazaar),
+    /// A product's saved history, read off the UI thread.
+    History(String, io::Result<Vec<Sample>>),
+    /// Recording a snapshot to the history store failed.
+    HistoryNotSaved(io::Error),
+}
 
-        let mut products = IndexMap::new();
-        for (k, v) in bazaar.products {
-            products.insert(k, v);
+/// Appends one sample at `ts` unless `history` already reaches that far,
+/// dropping the oldest once it holds [`HISTORY_CAPACITY`].
+fn push_sample(history: &mut VecDeque<Sample>, ts: i64, px: Prices) {
+    if history.back().is_some_and(|s| s.ts >= ts) {
+        return;
+    }
+    if history.len() == HISTORY_CAPACITY {
+        history.pop_front();
+    }
+    history.push_back(Sample {
+        ts,
+        buy: px.instant_buy,
+        sell: px.instant_sell,
+    });
+}
+
+/// Puts saved samples in front of those gathered while they were read,
+/// skipping any the live ones already cover.
+fn prepend_samples(history: &mut VecDeque<Sample>, saved: &[Sample]) {
+    let first = history.front().map_or(i64::MAX, |s| s.ts);
+    let older = &saved[..saved.partition_point(|s| s.ts < first)];
+    for s in older.iter().rev() {
+        if history.len() == HISTORY_CAPACITY {
+            break;
         }
+        history.push_front(*s);
+    }
+}
 
-        let index = products
-            .keys()
-            .map(|id| {
-                let display = pretty_name(id);
-                ProductIndexItem {
-                    id: id.clone(),
-                    display: display.clone(),
-                    norm_display: normalize(&display),
-                }
-            })
-            .collect();
+/// Same rules as `market::bazaar_flips`, but with the configured tax and
+/// volume floor rather than the SDK's fixed ones. The SDK filters on its own
+/// tax, so its result can't just be repriced; a test keeps the two in step.
+fn compute_flips(
+    products: &IndexMap<String, BazaarProduct>,
+    config: &Config,
+) -> HashMap<String, BazaarFlip> {
+    products
+        .iter()
+        .filter_map(|(id, product)| {
+            let spread = market::bazaar_spread(product)?;
+            let status = product.quick_status.as_ref()?;
+            if status.buy_moving_week < config.min_weekly_volume
+                || status.sell_moving_week < config.min_weekly_volume
+            {
+                return None;
+            }
+            let profit =
+                spread.instant_buy_price * (1.0 - config.tax_rate) - spread.instant_sell_price;
+            if profit <= 0.0 || spread.instant_sell_price <= 0.0 {
+                return None;
+            }
+            let flip = BazaarFlip {
+                product_id: id.clone(),
+                order_price: spread.instant_sell_price,
+                offer_price: spread.instant_buy_price,
+                profit_per_item: profit,
+                margin: profit / spread.instant_sell_price,
+                buy_moving_week: status.buy_moving_week,
+                sell_moving_week: status.sell_moving_week,
+            };
+            Some((id.clone(), flip))
+        })
+        .collect()
+}
 
-        let filtered_indices = (0..products.len()).collect();
+fn build_index(products: &IndexMap<String, BazaarProduct>) -> Vec<ProductIndexItem> {
+    products
+        .keys()
+        .map(|id| {
+            let info = items::lookup(id);
+            ProductIndexItem {
+                id: id.clone(),
+                norm_display: normalize(&info.name),
+                norm_aliases: info.aliases.iter().map(|a| normalize(a)).collect(),
+                display: info.name,
+                rarity: info.rarity,
+                category: categories::categorize(id),
+            }
+        })
+        .collect()
+}
+
+impl App {
+    pub fn new(source: Arc<dyn BazaarSource>, bazaar: Bazaar, config: Config) -> Self {
+        let data = BazaarData::new(bazaar, &config);
+        let filtered_indices = (0..data.index.len()).collect();
 
         Self {
             view: View::Search,
             status: "Loaded".into(),
-            data: BazaarData {
-                products,
-                last_updated: bazaar.last_updated,
-                index,
-                flips,
-            },
+            data,
             search: SearchState {
                 input: String::new(),
                 mode: SearchMode::Insert,
@@ -160,26 +394,98 @@ impl App {
                 selected_index: 0,
                 needs_filter: true,
                 last_input_change: Instant::now(),
-                sort_by_profit: false,
+                category: None,
+                query_error: None,
+                table: TableLayout::default(),
+                chooser: None,
             },
             detail: DetailState {
                 product_id: None,
-                history: VecDeque::with_capacity(256),
-                show_percent: false,
-                show_sma: true,
+                history: VecDeque::new(),
+                show_percent: config.show_percent,
+                show_sma: config.show_sma,
                 show_midline: false,
-                refresh_task: None,
-                cancel_tx: None,
+                show_ema: false,
+                show_bollinger: false,
+                show_twap: false,
+                show_envelope: false,
+                show_spread: false,
+                show_candles: false,
+                candle_interval: None,
+                show_depth: false,
+                depth_scroll: 0,
+                fill: None,
+                range: ChartRange::default(),
+                zoom: 0,
+                pan: 0,
+                return_view: View::Search,
             },
+            watch: WatchlistState {
+                list: Watchlist::default(),
+                selected_index: 0,
+            },
+            alerts: AlertsState {
+                book: AlertBook::default(),
+                selected_index: 0,
+                form: None,
+                flash_until: None,
+                bell_pending: false,
+            },
+            crafts: CraftsState::default(),
+            npc: NpcState::default(),
+            compare: CompareState::default(),
             update_tx: None,
-            client,
+            // Already validated when the config was loaded.
+            keymap: Keymap::from_config(&config.keys).unwrap_or_default(),
+            config,
+            help: None,
+            mouse: MouseState::default(),
+            overrides: Overrides::default(),
+            source,
+            history_store: None,
+            refresh_task: None,
+            cancel_tx: None,
+            refresh_now: Arc::new(Notify::new()),
         }
     }
 
-    pub fn set_update_sender(&mut self, tx: mpsc::UnboundedSender<BazaarProduct>) {
+    pub fn set_update_sender(&mut self, tx: mpsc::UnboundedSender<Update>) {
         self.update_tx = Some(tx);
     }
 
+    /// Where `reload_config` reads from and what it lays on top.
+    pub fn set_overrides(&mut self, overrides: Overrides) {
+        self.overrides = overrides;
+    }
+
+    /// Persists every refresh and seeds charts with what earlier sessions saw.
+    pub fn set_history_store(&mut self, store: HistoryStore) {
+        self.history_store = Some(store);
+    }
+
+    pub fn set_table_layout(&mut self, table: TableLayout) {
+        self.search.table = table;
+        self.apply_filter();
+    }
+
+    pub fn set_watchlist(&mut self, list: Watchlist) {
+        self.watch.list = list;
+    }
+
+    /// Rules that already hold on the startup snapshot count as tripped.
+    pub fn set_alerts(&mut self, mut book: AlertBook) {
+        book.seed(&self.data.products, &self.data.flips);
+        self.alerts.book = book;
+    }
+
+    /// Id of the highlighted search row, if any.
+    pub fn selected_product_id(&self) -> Option<&str> {
+        self.search
+            .filtered_indices
+            .get(self.search.selected_index)
+            .map(|&idx| self.data.index[idx].id.as_str())
+    }
+
     pub fn current_product(&self) -> Option<&BazaarProduct> {
         self.detail.product_id.as_ref().and_then(|id| self.data.products.get(id))
     }
@@ -211,28 +517,19 @@ impl App {
         }
     }
 
-    pub fn recompute_filter(&mut self) {
-        self.apply_filter();
-    }
-
     fn apply_filter(&mut self) {
-        if self.search.input.trim().is_empty() {
-            self.search.filtered_indices = (0..self.data.index.len()).collect();
-        } else {
-            let query = normalize(&self.search.input);
-            let mut scored: Vec<(usize, i32)> = self.data.index
-                .iter()
-                .enumerate()
-                .map(|(i, item)| (i, score_normalized(&query, &item.norm_display)))
-                .filter(|(_, score)| *score > crate::app::search::MIN_SCORE)
-                .collect();
-
-            scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
-            self.search.filtered_indices = scored.into_iter().map(|(i, _)| i).collect();
+        let query = Query::parse(&self.search.input);
+        self.search.filtered_indices = query.filter(&self.data);
+        self.search.query_error = query.error;
+        if let Some(category) = self.search.category {
+            let index = &self.data.index;
+            self.search
+                .filtered_indices
+                .retain(|&i| index[i].category == category);
         }
 
-        if self.search.sort_by_profit {
-            self.sort_filtered_by_profit();
+        if let Some(column) = self.search.table.sort() {
+            self.sort_filtered_by(column, self.search.table.descending());
         }
 
         let count = self.search.filtered_indices.len();
@@ -243,28 +540,53 @@ impl App {
         }
     }
 
-    fn sort_filtered_by_profit(&mut self) {
-        let profits: HashMap<usize, f64> = self
+    /// Stable, so ties keep relevance order; rows without a value sink
+    /// whichever way the column runs.
+    fn sort_filtered_by(&mut self, column: Column, descending: bool) {
+        let values: HashMap<usize, f64> = self
             .search
             .filtered_indices
             .iter()
-            .map(|&idx| (idx, self.flip_profit(idx)))
+            .filter_map(|&idx| {
+                let id = &self.data.index.get(idx)?.id;
+                let v = column.value(self.data.products.get(id)?, self.data.flips.get(id))?;
+                Some((idx, v))
+            })
             .collect();
 
-        self.search.filtered_indices.sort_by(|a_idx, b_idx| {
-            let a = profits.get(a_idx).copied().unwrap_or(0.0);
-            let b = profits.get(b_idx).copied().unwrap_or(0.0);
-            b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
-        });
+        self.search
+            .filtered_indices
+            .sort_by(|a, b| match (values.get(a), values.get(b)) {
+                (Some(a), Some(b)) if descending => b.total_cmp(a),
+                (Some(a), Some(b)) => a.total_cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
     }
 
-    /// Post-tax profit per item; rejected products score zero and sink.
-    fn flip_profit(&self, index: usize) -> f64 {
-        self.data
-            .index
-            .get(index)
-            .and_then(|item| self.data.flips.get(&item.id))
-            .map_or(0.0, |f| f.profit_per_item)
+    /// Applies a column layout change, reporting a failed save, and
+    /// re-sorts with the selection kept on the same product.
+    pub fn change_table(&mut self, change: impl FnOnce(&mut TableLayout) -> io::Result<()>) {
+        if let Err(e) = change(&mut self.search.table) {
+            self.status = format!("Columns not saved: {e}");
+        } else {
+            let table = &self.search.table;
+            self.status = match table.sort() {
+                Some(c) => format!(
+                    "Sorted by {} {}",
+                    c.label(),
+                    if table.descending() {
+                        "high to low"
+                    } else {
+                        "low to high"
+                    }
+                ),
+                None => "Sorted by relevance".into(),
+            };
+        }
+        let selected = self.selected_product_id().map(str::to_owned);
+        self.refilter_keeping(selected);
     }
 
     pub fn flip(&self, product_id: &str) -> Option<&BazaarFlip> {
@@ -294,24 +616,594 @@ impl App {
     }
 
     pub fn enter_detail(&mut self) {
-        if let Some(&idx) = self.search.filtered_indices.get(self.search.selected_index) {
-            let id = self.data.index[idx].id.clone();
-            self.detail.product_id = Some(id.clone());
-            self.detail.history.clear();
-            if let Some(p) = self.data.products.get(&id).and_then(prices) {
-                self.push_history(p.instant_buy, p.instant_sell);
-            }
-            self.view = View::Detail;
-            
-            self.start_refresh(id);
+        if let Some(id) = self.selected_product_id().map(str::to_owned) {
+            self.open_detail(id);
         }
     }
 
+    pub fn open_detail(&mut self, id: String) {
+        if self.view != View::Detail {
+            self.detail.return_view = self.view;
+        }
+        self.detail.product_id = Some(id.clone());
+        self.detail.history.clear();
+        self.detail.depth_scroll = 0;
+        self.detail.zoom = 0;
+        self.detail.pan = 0;
+        self.status = format!("Detail: {id}");
+        self.load_history(&id);
+        if let Some(p) = self.data.products.get(&id).and_then(prices) {
+            self.push_history(p.instant_buy, p.instant_sell);
+        }
+        self.view = View::Detail;
+    }
+
     pub fn exit_detail(&mut self) {
-        self.stop_refresh();
-        self.view = View::Search;
+        self.view = self.detail.return_view;
         self.detail.product_id = None;
         self.detail.history.clear();
+        self.detail.fill = None;
+    }
+
+    pub fn index_item(&self, product_id: &str) -> Option<&ProductIndexItem> {
+        self.data.index.iter().find(|item| item.id == product_id)
+    }
+
+    /// Display name for an id, falling back to the id for delisted products.
+    pub fn display_name<'a>(&'a self, product_id: &'a str) -> &'a str {
+        self.index_item(product_id)
+            .map_or(product_id, |item| item.display.as_str())
+    }
+
+    pub fn toggle_star(&mut self, product_id: &str) {
+        let name = self.display_name(product_id).to_owned();
+        self.status = match self.watch.list.toggle(product_id) {
+            Ok(true) => format!("Starred {name}"),
+            Ok(false) => format!("Unstarred {name}"),
+            Err(e) => format!("Watchlist not saved: {e}"),
+        };
+        let len = self.watch.list.products().len();
+        self.watch.selected_index = self.watch.selected_index.min(len.saturating_sub(1));
+    }
+
+    pub fn selected_watch_id(&self) -> Option<&str> {
+        self.watch
+            .list
+            .products()
+            .get(self.watch.selected_index)
+            .map(String::as_str)
+    }
+
+    pub fn move_watch_selection(&mut self, delta: isize) {
+        let len = self.watch.list.products().len() as isize;
+        if len == 0 {
+            return;
+        }
+        let idx = (self.watch.selected_index as isize + delta).clamp(0, len - 1);
+        self.watch.selected_index = idx as usize;
+    }
+
+    /// Selects `row` of the current view's list; false past the end.
+    pub fn select_row(&mut self, row: usize) -> bool {
+        match self.view {
+            View::Search if row < self.search.filtered_indices.len() => {
+                self.search.selected_index = row;
+                self.search.mode = SearchMode::Navigate;
+            }
+            View::Watchlist if row < self.watch.list.products().len() => {
+                self.watch.selected_index = row;
+            }
+            View::Alerts if row < self.alerts.book.rules().len() => {
+                self.alerts.selected_index = row;
+            }
+            View::Crafts => return self.select_craft_row(row),
+            View::Npc if row < self.npc_flips().len() => {
+                self.npc.selected_index = row;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn zoom_chart(&mut self, zoom_in: bool) {
+        self.detail.zoom = if zoom_in {
+            (self.detail.zoom + 1).min(MAX_CHART_ZOOM)
+        } else {
+            self.detail.zoom.saturating_sub(1)
+        };
+        self.status = match self.detail.zoom {
+            0 => format!("Chart: {}", self.detail.range.label()),
+            z => format!(
+                "Chart: {:.0}% of {}",
+                CHART_ZOOM_STEP.powi(z as i32) * 100.0,
+                self.detail.range.label()
+            ),
+        };
+    }
+
+    /// Switches to the next wider or narrower preset range, from the present.
+    pub fn step_chart_range(&mut self, wider: bool) {
+        self.detail.range = self.detail.range.step(wider);
+        self.detail.zoom = 0;
+        self.detail.pan = 0;
+        self.status = format!("Chart: {}", self.detail.range.label());
+    }
+
+    /// Auto, then each candle interval from the shortest, then auto again.
+    pub fn cycle_candle_interval(&mut self) {
+        self.detail.candle_interval = match self.detail.candle_interval {
+            None => Some(0),
+            Some(i) if i + 1 < CANDLE_INTERVALS.len() => Some(i + 1),
+            Some(_) => None,
+        };
+        self.status = match self.detail.candle_interval {
+            Some(i) => format!("Candles: {}", CANDLE_INTERVALS[i].1),
+            None => "Candles: auto".into(),
+        };
+    }
+
+    /// Moves the chart window back in time, or forward towards the present.
+    pub fn pan_chart(&mut self, back: bool) {
+        let window = |d: &DetailState| ChartWindow::new(&d.history, d.range, d.zoom, d.pan);
+        if back {
+            if window(&self.detail).is_some_and(|w| !w.at_oldest(&self.detail.history)) {
+                self.detail.pan += 1;
+            }
+        } else {
+            self.detail.pan = self.detail.pan.saturating_sub(1);
+        }
+        self.status = match (self.detail.pan, window(&self.detail)) {
+            (0, _) | (_, None) => "Chart: latest".into(),
+            (_, Some(w)) => format!("Chart: up to {}", fmt_time(w.end)),
+        };
+    }
+
+    pub fn enter_watch_detail(&mut self) {
+        if let Some(id) = self.selected_watch_id().map(str::to_owned) {
+            self.open_detail(id);
+        }
+    }
+
+    /// Swaps in a fresh snapshot, keeping the search filter and the
+    /// highlighted product where they were.
+    pub fn apply_snapshot(&mut self, bazaar: Bazaar) {
+        let selected = self.selected_product_id().map(str::to_owned);
+
+        self.data.last_updated = bazaar.last_updated;
+
+        let listing_changed = bazaar.products.len() != self.data.products.len()
+            || bazaar
+                .products
+                .keys()
+                .any(|id| !self.data.products.contains_key(id));
+        if listing_changed {
+            self.data
+                .products
+                .retain(|id, _| bazaar.products.contains_key(id));
+        }
+        for (_, p) in bazaar.products {
+            self.update_product(p);
+        }
+        if listing_changed {
+            self.data.index = build_index(&self.data.products);
+        }
+        self.data.flips = compute_flips(&self.data.products, &self.config);
+        self.check_craft_breakdown();
+        let npc_len = self.npc_flips().len();
+        self.npc.selected_index = self.npc.selected_index.min(npc_len.saturating_sub(1));
+
+        self.check_alerts();
+
+        // Fresh flips can reorder a profit sort, so always re-run the filter.
+        self.refilter_keeping(selected);
+    }
+
+    /// Switches the category tab, keeping the selection where it can.
+    pub fn set_category(&mut self, category: Option<&'static str>) {
+        self.search.category = category;
+        self.status = format!("Showing {}", category.unwrap_or("all products"));
+        let selected = self.selected_product_id().map(str::to_owned);
+        self.refilter_keeping(selected);
+    }
+
+    /// Moves `delta` tabs along the strip, wrapping past "All".
+    pub fn cycle_category(&mut self, delta: isize) {
+        let tabs: Vec<Option<&'static str>> = std::iter::once(None)
+            .chain(categories::names().map(Some))
+            .collect();
+        let at = tabs
+            .iter()
+            .position(|&t| t == self.search.category)
+            .unwrap_or(0);
+        let next = (at as isize + delta).rem_euclid(tabs.len() as isize) as usize;
+        self.set_category(tabs[next]);
+    }
+
+    fn refilter_keeping(&mut self, selected: Option<String>) {
+        self.apply_filter();
+        if let Some(pos) = selected.and_then(|id| {
+            self.search
+                .filtered_indices
+                .iter()
+                .position(|&idx| self.data.index[idx].id == id)
+        }) {
+            self.search.selected_index = pos;
+        }
+    }
+
+    /// Re-reads the config file, keeping the command-line overrides on top.
+    /// A bad file leaves the running config alone.
+    pub fn reload_config(&mut self) {
+        match self.overrides.load() {
+            Ok(config) => {
+                self.apply_config(config);
+                self.status = "Config reloaded".into();
+            }
+            Err(e) => self.status = format!("Config not reloaded: {e}"),
+        }
+    }
+
+    fn apply_config(&mut self, config: Config) {
+        let old = std::mem::replace(&mut self.config, config);
+        if self.config.keys != old.keys {
+            self.keymap = Keymap::from_config(&self.config.keys).unwrap_or_default();
+        }
+        if self.config.show_sma != old.show_sma {
+            self.detail.show_sma = self.config.show_sma;
+        }
+        if self.config.show_percent != old.show_percent {
+            self.detail.show_percent = self.config.show_percent;
+        }
+        if self.config.tax_rate != old.tax_rate
+            || self.config.min_weekly_volume != old.min_weekly_volume
+        {
+            let selected = self.selected_product_id().map(str::to_owned);
+            self.data.flips = compute_flips(&self.data.products, &self.config);
+            self.refilter_keeping(selected);
+            self.check_craft_breakdown();
+        }
+        let mut restart = self.config.refresh_secs != old.refresh_secs;
+        if self.config.request_timeout_secs != old.request_timeout_secs
+            && let Some(source) = self.source.with_timeout(self.config.request_timeout())
+        {
+            self.source = source;
+            restart = true;
+        }
+        if restart && self.refresh_task.is_some() {
+            self.start_refresh();
+        }
+    }
+
+    fn check_alerts(&mut self) {
+        let fired = self.alerts.book.evaluate(
+            self.data.last_updated,
+            &self.data.products,
+            &self.data.flips,
+        );
+        let Some(first) = fired.first() else {
+            return;
+        };
+        self.status = match fired.len() {
+            1 => format!("ALERT {} {}", first.product_id, first.message),
+            n => format!(
+                "ALERT {} {} (+{} more)",
+                first.product_id,
+                first.message,
+                n - 1
+            ),
+        };
+        self.alerts.flash_until = Some(Instant::now() + ALERT_FLASH);
+        self.alerts.bell_pending = true;
+    }
+
+    pub fn alert_flashing(&self) -> bool {
+        self.alerts.flash_until.is_some_and(|t| Instant::now() < t)
+    }
+
+    /// Priceable recipes under the current pricing, best first.
+    pub fn craft_quotes(&self) -> Vec<CraftQuote> {
+        crafts::ranked(
+            &self.data.products,
+            self.crafts.inputs,
+            self.crafts.output,
+            self.config.tax_rate,
+            self.crafts.rank,
+        )
+    }
+
+    /// Row of the selected recipe in `quotes`, or the first.
+    pub fn craft_row(&self, quotes: &[CraftQuote]) -> usize {
+        self.crafts
+            .selected
+            .and_then(|r| quotes.iter().position(|q| q.recipe == r))
+            .unwrap_or(0)
+    }
+
+    /// Moves through the recipes, or through the breakdown when drilled in.
+    pub fn move_craft_selection(&mut self, delta: isize) {
+        if let Some(part) = self.crafts.part {
+            let last = self.craft_parts().len().saturating_sub(1) as isize;
+            self.crafts.part = Some((part as isize + delta).clamp(0, last) as usize);
+            return;
+        }
+        let quotes = self.craft_quotes();
+        if quotes.is_empty() {
+            return;
+        }
+        let row = (self.craft_row(&quotes) as isize + delta).clamp(0, quotes.len() as isize - 1);
+        self.crafts.selected = Some(quotes[row as usize].recipe);
+    }
+
+    fn select_craft_row(&mut self, row: usize) -> bool {
+        if self.crafts.part.is_some() {
+            if row >= self.craft_parts().len() {
+                return false;
+            }
+            self.crafts.part = Some(row);
+        } else {
+            let Some(q) = self.craft_quotes().get(row).copied() else {
+                return false;
+            };
+            self.crafts.selected = Some(q.recipe);
+        }
+        true
+    }
+
+    /// The selected recipe's inputs, then its output, with amounts per craft.
+    pub fn craft_parts(&self) -> Vec<(&'static str, u32)> {
+        let quotes = self.craft_quotes();
+        let Some(recipe) = quotes
+            .get(self.craft_row(&quotes))
+            .and_then(|q| crafts::recipes().get(q.recipe))
+        else {
+            return Vec::new();
+        };
+        recipe
+            .inputs
+            .iter()
+            .map(|(id, &n)| (id.as_str(), n))
+            .chain(std::iter::once((recipe.output.as_str(), recipe.count)))
+            .collect()
+    }
+
+    /// Leaves the breakdown once a refresh or repricing drops its recipe
+    /// from the quotes, so it never shows or opens another recipe's parts.
+    pub fn check_craft_breakdown(&mut self) {
+        if self.crafts.part.is_none() {
+            return;
+        }
+        let quotes = self.craft_quotes();
+        if !self
+            .crafts
+            .selected
+            .is_some_and(|r| quotes.iter().any(|q| q.recipe == r))
+        {
+            self.crafts.part = None;
+            self.status = "That recipe can no longer be priced".into();
+        }
+    }
+
+    /// Drills into the selected recipe, or opens the book of the part
+    /// under the cursor.
+    pub fn open_craft(&mut self) {
+        match self.crafts.part {
+            None => {
+                let quotes = self.craft_quotes();
+                if let Some(q) = quotes.get(self.craft_row(&quotes)) {
+                    self.crafts.selected = Some(q.recipe);
+                    self.crafts.part = Some(0);
+                }
+            }
+            Some(part) => {
+                if let Some(&(id, _)) = self.craft_parts().get(part) {
+                    self.open_detail(id.to_string());
+                    self.detail.show_depth = true;
+                }
+            }
+        }
+    }
+
+    pub fn toggle_craft_rank(&mut self) {
+        self.crafts.rank = match self.crafts.rank {
+            CraftRank::PerCraft => CraftRank::PerHour,
+            CraftRank::PerHour => CraftRank::PerCraft,
+        };
+        self.status = match self.crafts.rank {
+            CraftRank::PerCraft => "Ranked by profit per craft".into(),
+            CraftRank::PerHour => "Ranked by profit per hour of volume".into(),
+        };
+    }
+
+    /// Marks `product_id` for the compare view, or unmarks it.
+    pub fn toggle_mark(&mut self, product_id: &str) {
+        let name = self.display_name(product_id).to_owned();
+        let marked = &mut self.compare.marked;
+        if let Some(i) = marked.iter().position(|m| m == product_id) {
+            marked.remove(i);
+            self.status = format!("Unmarked {name} ({}/{MAX_COMPARE})", marked.len());
+        } else if marked.len() == MAX_COMPARE {
+            self.status = format!("Compare holds {MAX_COMPARE} products; unmark one first");
+        } else {
+            marked.push(product_id.to_owned());
+            self.status = format!("Marked {name} ({}/{MAX_COMPARE})", marked.len());
+        }
+        self.compare.selected = self
+            .compare
+            .selected
+            .min(self.compare.marked.len().saturating_sub(1));
+    }
+
+    /// Opens the compare view with each marked product's saved history.
+    pub fn open_compare(&mut self) {
+        if self.compare.marked.len() < 2 {
+            self.status = "Mark at least two products to compare".into();
+            return;
+        }
+        let ts = self.sample_ts();
+        let mut histories = Vec::with_capacity(self.compare.marked.len());
+        for id in &self.compare.marked {
+            let mut history = VecDeque::new();
+            if let Some(px) = self.data.products.get(id).and_then(prices) {
+                push_sample(&mut history, ts, px);
+            }
+            self.load_history(id);
+            histories.push(history);
+        }
+        self.compare.history = histories;
+        self.view = View::Compare;
+    }
+
+    pub fn exit_compare(&mut self) {
+        self.compare.history.clear();
+        self.view = View::Search;
+    }
+
+    pub fn move_compare_selection(&mut self, delta: isize) {
+        let len = self.compare.marked.len() as isize;
+        if len == 0 {
+            return;
+        }
+        self.compare.selected = (self.compare.selected as isize + delta).clamp(0, len - 1) as usize;
+    }
+
+    /// Drops the selected product from the comparison, leaving the view
+    /// once fewer than two are left.
+    pub fn unmark_selected(&mut self) {
+        let i = self.compare.selected;
+        if i >= self.compare.marked.len() {
+            return;
+        }
+        let id = self.compare.marked[i].clone();
+        self.toggle_mark(&id);
+        if i < self.compare.history.len() {
+            self.compare.history.remove(i);
+        }
+        if self.compare.marked.len() < 2 {
+            self.exit_compare();
+        }
+    }
+
+    pub fn open_compare_detail(&mut self) {
+        if let Some(id) = self.compare.marked.get(self.compare.selected).cloned() {
+            self.open_detail(id);
+        }
+    }
+
+    pub fn npc_flips(&self) -> Vec<NpcFlip> {
+        npc::ranked(&self.data.products)
+    }
+
+    pub fn move_npc_selection(&mut self, delta: isize) {
+        let len = self.npc_flips().len() as isize;
+        if len == 0 {
+            return;
+        }
+        self.npc.selected_index =
+            (self.npc.selected_index as isize + delta).clamp(0, len - 1) as usize;
+    }
+
+    pub fn enter_npc_detail(&mut self) {
+        if let Some(flip) = self.npc_flips().into_iter().nth(self.npc.selected_index) {
+            self.open_detail(flip.product_id);
+        }
+    }
+
+    /// Whether an alert fired since the last call; the event loop rings the bell.
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.alerts.bell_pending)
+    }
+
+    /// Opens the alerts view with a new-rule form, prefilled with `product`.
+    pub fn new_alert(&mut self, product: Option<&str>) {
+        self.alerts.form = Some(AlertForm::new(product));
+        self.view = View::Alerts;
+    }
+
+    pub fn edit_selected_alert(&mut self) {
+        let i = self.alerts.selected_index;
+        if let Some(rule) = self.alerts.book.rules().get(i) {
+            self.alerts.form = Some(AlertForm::edit(i, rule));
+        }
+    }
+
+    pub fn submit_alert_form(&mut self) {
+        let Some(form) = self.alerts.form.as_mut() else {
+            return;
+        };
+        let rule = match form.to_rule(&self.data.products) {
+            Ok(rule) => rule,
+            Err(e) => {
+                form.error = Some(e);
+                return;
+            }
+        };
+        let description = rule.describe();
+        let editing = form.editing;
+        self.alerts.form = None;
+        self.status = match self.alerts.book.upsert(editing, rule) {
+            Ok(()) => format!("Alert saved: {description}"),
+            Err(e) => format!("Alert not saved: {e}"),
+        };
+        if editing.is_none() {
+            self.alerts.selected_index = self.alerts.book.rules().len() - 1;
+        }
+    }
+
+    pub fn delete_selected_alert(&mut self) {
+        let i = self.alerts.selected_index;
+        if i >= self.alerts.book.rules().len() {
+            return;
+        }
+        self.status = match self.alerts.book.remove(i) {
+            Ok(()) => "Alert deleted".into(),
+            Err(e) => format!("Alert not saved: {e}"),
+        };
+        let len = self.alerts.book.rules().len();
+        self.alerts.selected_index = self.alerts.selected_index.min(len.saturating_sub(1));
+    }
+
+    pub fn move_alert_selection(&mut self, delta: isize) {
+        let len = self.alerts.book.rules().len() as isize;
+        if len == 0 {
+            return;
+        }
+        let idx = (self.alerts.selected_index as isize + delta).clamp(0, len - 1);
+        self.alerts.selected_index = idx as usize;
+    }
+
+    pub fn apply_update(&mut self, update: Update) {
+        match update {
+            Update::Snapshot(bazaar) => self.apply_snapshot(bazaar),
+            Update::History(id, Ok(samples)) => self.merge_saved_history(&id, &samples),
+            Update::History(_, Err(e)) => self.status = format!("History unavailable: {e}"),
+            Update::HistoryNotSaved(e) => self.status = format!("History not saved: {e}"),
+        }
+    }
+
+    /// Reads `product_id`'s saved history on the blocking pool; it comes
+    /// back as an [`Update::History`].
+    fn load_history(&self, product_id: &str) {
+        let (Some(store), Some(tx)) = (self.history_store.clone(), self.update_tx.clone()) else {
+            return;
+        };
+        let id = product_id.to_owned();
+        tokio::task::spawn_blocking(move || {
+            let samples = store.load(&id, HISTORY_CAPACITY);
+            let _ = tx.send(Update::History(id, samples));
+        });
+    }
+
+    /// Loads can land after the chart was closed or switched, so only
+    /// charts still showing the product take them.
+    fn merge_saved_history(&mut self, product_id: &str, samples: &[Sample]) {
+        if self.detail.product_id.as_deref() == Some(product_id) {
+            prepend_samples(&mut self.detail.history, samples);
+        }
+        if let Some(i) = self.compare.marked.iter().position(|m| m == product_id)
+            && let Some(history) = self.compare.history.get_mut(i)
+        {
+            prepend_samples(history, samples);
+        }
     }
 
     pub fn update_product(&mut self, p: BazaarProduct) {
@@ -323,37 +1215,95 @@ impl App {
             }
             self.status = "Updated".into();
         }
+        // Compare history is only kept while the view is open.
+        if !self.compare.history.is_empty()
+            && let Some(i) = self.compare.marked.iter().position(|m| *m == id)
+            && let Some(px) = prices(&p)
+        {
+            let ts = self.sample_ts();
+            push_sample(&mut self.compare.history[i], ts, px);
+        }
 
         self.data.products.insert(id, p);
     }
 
     fn push_history(&mut self, buy: f64, sell: f64) {
-        let now = Instant::now();
-        if self.detail.history.len() == self.detail.history.capacity() {
-            self.detail.history.pop_front();
+        let ts = self.sample_ts();
+        push_sample(
+            &mut self.detail.history,
+            ts,
+            Prices {
+                instant_buy: buy,
+                instant_sell: sell,
+            },
+        );
+    }
+
+    /// The snapshot time, so re-polling an unchanged snapshot does not stack
+    /// duplicate points.
+    fn sample_ts(&self) -> i64 {
+        if self.data.last_updated > 0 {
+            self.data.last_updated
+        } else {
+            now_ms()
         }
-        self.detail.history.push_back((now, buy, sell));
     }
 
-    fn start_refresh(&mut self, product_id: String) {
+    /// Starts the app-wide poller that refetches the whole bazaar.
+    pub fn start_refresh(&mut self) {
         self.stop_refresh();
 
         let (tx, mut rx) = oneshot::channel::<()>();
-        self.detail.cancel_tx = Some(tx);
+        self.cancel_tx = Some(tx);
         let outbound = self.update_tx.clone();
-        let client = self.client.clone();
-        let pid_task = product_id.clone();
+        let source = self.source.clone();
+        let mut writer = self.history_store.clone().map(HistoryWriter::new);
+        let interval = self.config.refresh_interval();
+        let refresh_now = self.refresh_now.clone();
 
         let handle = tokio::spawn(async move {
-            let mut ticker = tokio::time::interval(Duration::from_secs(3));
+            // The first tick fires at once, so history starts with a fresh
+            // snapshot instead of waiting out a whole interval.
+            let mut ticker = tokio::time::interval(interval);
             loop {
                 tokio::select! {
+                    _ = refresh_now.notified() => {
+                        ticker.reset_immediately();
+                    }
                     _ = ticker.tick() => {
-                        if let Ok(bazaar) = client.skyblock_bazaar().await
-                            && let Some(p) = bazaar.products.get(&pid_task)
-                            && let Some(out) = &outbound
-                        {
-                            let _ = out.send(p.clone());
+                        let Ok(bazaar) = source.fetch().await else {
+                            continue;
+                        };
+                        let (bazaar, failed) = match writer.take() {
+                            Some(mut w) => {
+                                // Disk writes stay off the async workers.
+                                let written = tokio::task::spawn_blocking(move || {
+                                    let result = w.record(bazaar.last_updated, bazaar.products.values());
+                                    (w, bazaar, result)
+                                })
+                                .await;
+                                match written {
+                                    Ok((w, bazaar, result)) => {
+                                        writer = Some(w);
+                                        (bazaar, result.err())
+                                    }
+                                    // The snapshot went down with the writer; keep polling
+                                    // without history rather than stopping altogether.
+                                    Err(e) => {
+                                        if let Some(out) = &outbound {
+                                            let _ = out.send(Update::HistoryNotSaved(io::Error::other(e)));
+                                        }
+                                        continue;
+                                    }
+                                }
+                            }
+                            None => (bazaar, None),
+                        };
+                        if let Some(out) = &outbound {
+                            let _ = out.send(Update::Snapshot(bazaar));
+                            if let Some(e) = failed {
+                                let _ = out.send(Update::HistoryNotSaved(e));
+                            }
                         }
                     }
                     _ = &mut rx => {
@@ -362,40 +1312,33 @@ impl App {
                 }
             }
         });
-        self.detail.refresh_task = Some(handle);
-        self.status = format!("Detail: {} (refreshing every 3s)", product_id);
+        self.refresh_task = Some(handle);
     }
 
     pub fn stop_refresh(&mut self) {
-        if let Some(tx) = self.detail.cancel_tx.take() {
+        if let Some(tx) = self.cancel_tx.take() {
             let _ = tx.send(());
         }
-        if let Some(h) = self.detail.refresh_task.take() {
+        if let Some(h) = self.refresh_task.take() {
             h.abort();
         }
     }
 
+    /// Fetches now through the poller, which also writes history.
     pub fn manual_refresh(&mut self) {
-        if let Some(id) = &self.detail.product_id {
-            let id = id.clone();
-            let outbound = self.update_tx.clone();
-            let client = self.client.clone();
-            tokio::spawn(async move {
-                if let Ok(bazaar) = client.skyblock_bazaar().await
-                    && let Some(p) = bazaar.products.get(&id)
-                    && let Some(out) = &outbound
-                {
-                    let _ = out.send(p.clone());
-                }
-            });
-            self.status = "Refreshing...".into();
+        if self.refresh_task.is_some() {
+            self.refresh_now.notify_one();
+        } else {
+            self.start_refresh();
         }
+        self.status = "Refreshing...".into();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::source::{self, Scripted};
     use hypixel::models::skyblock::{BazaarOrder, BazaarQuickStatus};
 
     fn order(price: f64) -> BazaarOrder {
@@ -492,4 +1435,356 @@ mod tests {
 
         assert!(px.spread() < 0.0);
     }
+
+    fn idle() -> Arc<dyn BazaarSource> {
+        Arc::new(Scripted::default())
+    }
+
+    fn bazaar(last_updated: i64, books: &[(&str, f64, f64)]) -> Bazaar {
+        Bazaar {
+            last_updated,
+            products: books
+                .iter()
+                .map(|&(id, ask, bid)| {
+                    let mut p = product(ask, bid);
+                    p.product_id = id.into();
+                    (id.to_string(), p)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn a_snapshot_swap_keeps_the_selection_on_the_same_product() {
+        let mut app = App::new(
+            idle(),
+            bazaar(
+                1,
+                &[("ENCHANTED_DIAMOND", 10.0, 9.0), ("DIAMOND", 2.0, 1.0)],
+            ),
+            Config::default(),
+        );
+        app.search.input = "diamond".into();
+        app.apply_filter();
+        let pos = app
+            .search
+            .filtered_indices
+            .iter()
+            .position(|&i| app.data.index[i].id == "ENCHANTED_DIAMOND")
+            .unwrap();
+        app.search.selected_index = pos;
+
+        app.apply_snapshot(bazaar(
+            2,
+            &[
+                ("ENCHANTED_DIAMOND", 12.0, 11.0),
+                ("DIAMOND", 2.0, 1.0),
+                ("DIAMOND_BLOCK", 20.0, 18.0),
+            ],
+        ));
+
+        assert_eq!(app.data.last_updated, 2);
+        assert_eq!(app.search.input, "diamond");
+        assert_eq!(app.selected_product_id(), Some("ENCHANTED_DIAMOND"));
+        assert_eq!(app.data.index.len(), 3);
+        let px = prices(&app.data.products["ENCHANTED_DIAMOND"]).unwrap();
+        assert_eq!(px.instant_buy, 12.0);
+    }
+
+    #[test]
+    fn a_column_sort_reorders_results_and_keeps_the_selection() {
+        let mut app = App::new(
+            idle(),
+            bazaar(1, &[("A", 5.0, 4.0), ("B", 9.0, 8.0), ("C", 2.0, 1.0)]),
+            Config::default(),
+        );
+        app.apply_filter();
+        let ids = |app: &App| -> Vec<String> {
+            app.search
+                .filtered_indices
+                .iter()
+                .map(|&i| app.data.index[i].id.clone())
+                .collect()
+        };
+        app.search.selected_index = ids(&app).iter().position(|id| id == "A").unwrap();
+
+        app.change_table(|t| t.click_sort(Column::Buy));
+        assert_eq!(ids(&app), ["B", "A", "C"]);
+        app.change_table(|t| t.click_sort(Column::Buy));
+        assert_eq!(ids(&app), ["C", "A", "B"]);
+        assert_eq!(app.selected_product_id(), Some("A"));
+    }
+
+    #[test]
+    fn delisted_products_leave_the_index() {
+        let mut app = App::new(
+            idle(),
+            bazaar(1, &[("A", 2.0, 1.0), ("B", 2.0, 1.0)]),
+            Config::default(),
+        );
+
+        app.apply_snapshot(bazaar(2, &[("A", 2.0, 1.0)]));
+
+        assert_eq!(app.data.index.len(), 1);
+        assert_eq!(app.search.filtered_indices, vec![0]);
+    }
+
+    #[test]
+    fn compare_takes_two_to_four_marked_products() {
+        let books: Vec<_> = ["A", "B", "C", "D", "E"].map(|id| (id, 2.0, 1.0)).into();
+        let mut app = App::new(idle(), bazaar(1, &books), Config::default());
+
+        app.toggle_mark("A");
+        app.open_compare();
+        assert_eq!(app.view, View::Search, "one product is nothing to compare");
+
+        for id in ["B", "C", "D", "E"] {
+            app.toggle_mark(id);
+        }
+        assert_eq!(app.compare.marked, ["A", "B", "C", "D"]);
+        app.toggle_mark("B");
+        assert_eq!(app.compare.marked, ["A", "C", "D"]);
+
+        app.open_compare();
+        assert_eq!(app.view, View::Compare);
+        assert_eq!(app.compare.history.len(), 3);
+        app.apply_snapshot(bazaar(
+            2,
+            &[("A", 3.0, 2.0), ("C", 2.0, 1.0), ("D", 2.0, 1.0)],
+        ));
+        assert!(app.compare.history.iter().all(|h| !h.is_empty()));
+    }
+
+    #[test]
+    fn saved_history_lands_behind_the_live_samples() {
+        let mut app = App::new(idle(), bazaar(10, &[("A", 2.0, 1.0)]), Config::default());
+        app.open_detail("A".into());
+        let saved: Vec<Sample> = [4, 8, 10, 12]
+            .map(|ts| Sample {
+                ts,
+                buy: 3.0,
+                sell: 2.0,
+            })
+            .into();
+
+        app.apply_update(Update::History("A".into(), Ok(saved)));
+        app.apply_update(Update::History(
+            "B".into(),
+            Ok(vec![Sample {
+                ts: 1,
+                buy: 1.0,
+                sell: 1.0,
+            }]),
+        ));
+
+        let ts: Vec<i64> = app.detail.history.iter().map(|s| s.ts).collect();
+        assert_eq!(ts, [4, 8, 10]);
+        assert_eq!(
+            app.detail.history[2].buy, 2.0,
+            "the live sample wins the overlap"
+        );
+    }
+
+    #[tokio::test]
+    async fn the_refresh_task_feeds_snapshots_back_into_the_app() {
+        let source = Arc::new(Scripted::new([bazaar(2, &[("A", 3.0, 1.0)])]));
+        let mut app = App::new(source, bazaar(1, &[("A", 2.0, 1.0)]), Config::default());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.set_update_sender(tx);
+
+        app.start_refresh();
+        let next = rx.recv().await.expect("first tick fetches at once");
+        app.stop_refresh();
+        assert!(matches!(next, Update::Snapshot(_)));
+        app.apply_update(next);
+
+        assert_eq!(app.data.last_updated, 2);
+        assert_eq!(prices(&app.data.products["A"]).unwrap().instant_buy, 3.0);
+    }
+
+    #[tokio::test]
+    async fn history_write_failures_reach_the_status_bar() {
+        let dir = std::env::temp_dir().join(format!(
+            "bazaar-tui-state-unwritable-{}",
+            std::process::id()
+        ));
+        let store = HistoryStore::open(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let source = Arc::new(Scripted::new([bazaar(2, &[("A", 3.0, 1.0)])]));
+        let mut app = App::new(source, bazaar(1, &[("A", 2.0, 1.0)]), Config::default());
+        app.set_history_store(store);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.set_update_sender(tx);
+
+        app.start_refresh();
+        let snapshot = rx.recv().await.unwrap();
+        let failure = rx.recv().await.unwrap();
+        app.stop_refresh();
+
+        assert!(matches!(snapshot, Update::Snapshot(_)));
+        app.apply_update(failure);
+        assert!(
+            app.status.starts_with("History not saved"),
+            "{}",
+            app.status
+        );
+    }
+
+    #[test]
+    fn a_new_timeout_rebuilds_the_client() {
+        let client: Arc<dyn BazaarSource> =
+            Arc::new(source::live_client(Config::default().request_timeout()));
+        let mut app = App::new(client.clone(), bazaar(1, &[]), Config::default());
+
+        app.apply_config(Config::default());
+        assert!(
+            Arc::ptr_eq(&app.source, &client),
+            "unchanged timeout keeps the client"
+        );
+        app.apply_config(Config {
+            request_timeout_secs: 30,
+            ..Config::default()
+        });
+        assert!(!Arc::ptr_eq(&app.source, &client));
+    }
+
+    #[tokio::test]
+    async fn manual_refreshes_reach_history() {
+        let dir =
+            std::env::temp_dir().join(format!("bazaar-tui-state-manual-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = HistoryStore::open(&dir).unwrap();
+        let source = Arc::new(Scripted::new([
+            bazaar(2, &[("A", 3.0, 1.0)]),
+            bazaar(3, &[("A", 4.0, 1.0)]),
+        ]));
+        let mut app = App::new(source, bazaar(1, &[("A", 2.0, 1.0)]), Config::default());
+        app.set_history_store(store.clone());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        app.set_update_sender(tx);
+
+        app.start_refresh();
+        assert!(matches!(rx.recv().await.unwrap(), Update::Snapshot(_)));
+        app.manual_refresh();
+        let next = rx.recv().await.unwrap();
+        app.stop_refresh();
+
+        assert!(matches!(next, Update::Snapshot(ref b) if b.last_updated == 3));
+        let saved: Vec<i64> = store.load("A", 10).unwrap().iter().map(|s| s.ts).collect();
+        assert_eq!(saved, [2, 3]);
+    }
+
+    #[test]
+    fn alerts_already_holding_at_startup_do_not_fire() {
+        use crate::app::alerts::{AlertRule, Comparison, Metric};
+        let mut app = App::new(idle(), bazaar(1, &[("A", 300.0, 200.0)]), Config::default());
+        let mut book = AlertBook::default();
+        let rule = AlertRule {
+            product: Some("A".into()),
+            metric: Metric::InstantSell,
+            comparison: Comparison::Above,
+            threshold: 100.0,
+        };
+        book.upsert(None, rule).unwrap();
+        app.set_alerts(book);
+
+        app.apply_snapshot(bazaar(2, &[("A", 300.0, 210.0)]));
+
+        assert!(!app.alert_flashing());
+        assert_eq!(app.alerts.book.log().count(), 0);
+    }
+
+    #[test]
+    fn a_breakdown_whose_recipe_drops_out_closes() {
+        let books = [
+            ("ENCHANTED_DIAMOND", 1_000.0, 990.0),
+            ("ENCHANTED_DIAMOND_BLOCK", 170_000.0, 165_000.0),
+        ];
+        let mut app = App::new(idle(), bazaar(1, &books), Config::default());
+        app.view = View::Crafts;
+        app.open_craft();
+        app.move_craft_selection(1);
+        assert_eq!(app.crafts.part, Some(1));
+
+        app.apply_snapshot(bazaar(2, &books[..1]));
+
+        assert_eq!(app.crafts.part, None);
+        assert!(
+            app.craft_parts().is_empty(),
+            "no other recipe's parts stand in"
+        );
+    }
+
+    #[test]
+    fn the_npc_selection_follows_a_shrinking_list() {
+        let npc = npc::npc_price("ENCHANTED_DIAMOND").unwrap();
+        let cheap = [("ENCHANTED_DIAMOND", npc - 10.0, npc - 20.0)];
+        let mut app = App::new(idle(), bazaar(1, &cheap), Config::default());
+        app.npc.selected_index = 5;
+
+        app.apply_snapshot(bazaar(2, &cheap));
+        assert_eq!(app.npc.selected_index, 0);
+        app.enter_npc_detail();
+        assert_eq!(app.detail.product_id.as_deref(), Some("ENCHANTED_DIAMOND"));
+    }
+
+    #[test]
+    fn flips_match_the_sdk_at_its_own_tax_and_floor() {
+        let listed = |id: &str, ask, bid, weekly| {
+            let mut p = with_quick_status(product(ask, bid), ask, bid);
+            p.product_id = id.into();
+            if let Some(qs) = p.quick_status.as_mut() {
+                qs.product_id = id.into();
+                qs.buy_moving_week = weekly;
+                qs.sell_moving_week = weekly;
+            }
+            (id.to_string(), p)
+        };
+        let snapshot = Bazaar {
+            last_updated: 1,
+            products: [
+                listed("WIDE", 120.0, 100.0, 5_000),
+                listed("THIN", 101.0, 100.0, 5_000),
+                listed("ILLIQUID", 200.0, 100.0, 10),
+                listed("FREE", 10.0, 0.0, 5_000),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let config = Config::default();
+        assert_eq!(config.tax_rate, market::BAZAAR_TAX);
+
+        let products: IndexMap<_, _> = snapshot.products.clone().into_iter().collect();
+        let ours = compute_flips(&products, &config);
+        let sdk = market::bazaar_flips(&snapshot, config.min_weekly_volume);
+
+        assert_eq!(ours.len(), sdk.len());
+        for flip in &sdk {
+            assert_eq!(ours.get(&flip.product_id), Some(flip));
+        }
+        assert!(ours.contains_key("WIDE"));
+    }
+
+    #[test]
+    fn a_config_change_reprices_flips_with_the_new_tax() {
+        let mut snapshot = bazaar(1, &[]);
+        let p = with_quick_status(product(101.0, 100.0), 101.0, 100.0);
+        snapshot.products.insert("THIN".into(), p);
+        let config = Config {
+            min_weekly_volume: 0,
+            ..Config::default()
+        };
+        let mut app = App::new(idle(), snapshot, config.clone());
+        assert!(
+            app.flip("THIN").is_none(),
+            "1% spread is eaten by the 1.25% tax"
+        );
+
+        app.apply_config(Config {
+            tax_rate: 0.0,
+            ..config
+        });
+
+        assert_eq!(app.flip("THIN").map(|f| f.profit_per_item), Some(1.0));
+    }
 }
@@ -0,0 +1,105 @@
+use crate::util::config_file;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WatchlistFile {
+    products: Vec<String>,
+}
+
+/// Starred product ids in the order they were starred, saved on every change.
+#[derive(Debug, Default)]
+pub struct Watchlist {
+    products: Vec<String>,
+    /// `None` keeps the list in memory only.
+    path: Option<PathBuf>,
+}
+
+impl Watchlist {
+    /// Loads `watchlist.toml` from the config dir; a missing file is an empty
+    /// list. So is one that can't be read, which comes back with the error
+    /// and is left alone until the list changes.
+    pub fn load_default() -> (Self, Option<io::Error>) {
+        let Some(path) = config_file("watchlist.toml") else {
+            return (Self::default(), None);
+        };
+        match Self::load(path.clone()) {
+            Ok(list) => (list, None),
+            Err(e) => (
+                Self {
+                    path: Some(path),
+                    ..Self::default()
+                },
+                Some(e),
+            ),
+        }
+    }
+
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let file: WatchlistFile = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(io::Error::other)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => WatchlistFile::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            products: file.products,
+            path: Some(path),
+        })
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = WatchlistFile {
+            products: self.products.clone(),
+        };
+        fs::write(path, toml::to_string(&file).map_err(io::Error::other)?)
+    }
+
+    pub fn products(&self) -> &[String] {
+        &self.products
+    }
+
+    pub fn contains(&self, product_id: &str) -> bool {
+        self.products.iter().any(|p| p == product_id)
+    }
+
+    /// Stars or unstars a product and saves; returns whether it is now starred.
+    pub fn toggle(&mut self, product_id: &str) -> io::Result<bool> {
+        let starred = if let Some(pos) = self.products.iter().position(|p| p == product_id) {
+            self.products.remove(pos);
+            false
+        } else {
+            self.products.push(product_id.to_string());
+            true
+        };
+        self.save()?;
+        Ok(starred)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stars_survive_a_reload() {
+        let path =
+            std::env::temp_dir().join(format!("bazaar-tui-watchlist-{}.toml", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut list = Watchlist::load(path.clone()).unwrap();
+        assert!(list.toggle("ENCHANTED_DIAMOND").unwrap());
+        assert!(list.toggle("BOOSTER_COOKIE").unwrap());
+        assert!(!list.toggle("ENCHANTED_DIAMOND").unwrap());
+
+        let reloaded = Watchlist::load(path).unwrap();
+        assert_eq!(reloaded.products(), ["BOOSTER_COOKIE"]);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::app::search::rank;
+use crate::app::state::{BazaarData, prices};
+use crate::util::{fmt_compact, fmt_pct, fmt_price, truncate};
+use clap::Subcommand;
+use hypixel::util::market::BazaarFlip;
+use serde::Serialize;
+use std::error::Error;
+
+/// One-shot commands that print and exit instead of opening the TUI.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Show prices and flip stats for the best match of a query
+    Price {
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+    },
+    /// List the most profitable order flips
+    Flips {
+        /// How many flips to print
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
+    /// List products matching a query, best match first
+    Search {
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+        /// How many matches to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct PriceRow<'a> {
+    product_id: &'a str,
+    name: &'a str,
+    instant_buy: f64,
+    instant_sell: f64,
+    spread: f64,
+    spread_pct: f64,
+    flip: Option<&'a BazaarFlip>,
+}
+
+#[derive(Debug, Serialize)]
+struct FlipRow<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    flip: &'a BazaarFlip,
+}
+
+pub fn run(command: Command, json: bool, data: &BazaarData) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Price { query } => {
+            let query = query.join(" ");
+            let row = rank(&query, &data.index)
+                .into_iter()
+                .find_map(|i| price_row(data, i))
+                .ok_or_else(|| format!("no priced product matches {query:?}"))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&row)?);
+            } else {
+                print_price(&row);
+            }
+        }
+        Command::Flips { top } => {
+            let mut flips: Vec<&BazaarFlip> = data.flips.values().collect();
+            flips.sort_by(|a, b| b.profit_per_item.total_cmp(&a.profit_per_item));
+            flips.truncate(top);
+            let rows: Vec<FlipRow> = flips
+                .into_iter()
+                .map(|flip| FlipRow {
+                    name: display_name(data, &flip.product_id),
+                    flip,
+                })
+                .collect();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else {
+                print_flips(&rows);
+            }
+        }
+        Command::Search { query, limit } => {
+            let rows: Vec<PriceRow> = rank(&query.join(" "), &data.index)
+                .into_iter()
+                .filter_map(|i| price_row(data, i))
+                .take(limit)
+                .collect();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else {
+                print_search(&rows);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn price_row(data: &BazaarData, index: usize) -> Option<PriceRow<'_>> {
+    let item = &data.index[index];
+    let px = data.products.get(&item.id).and_then(prices)?;
+    Some(PriceRow {
+        product_id: &item.id,
+        name: &item.display,
+        instant_buy: px.instant_buy,
+        instant_sell: px.instant_sell,
+        spread: px.spread(),
+        spread_pct: px.spread_pct(),
+        flip: data.flips.get(&item.id),
+    })
+}
+
+fn display_name<'a>(data: &'a BazaarData, product_id: &'a str) -> &'a str {
+    data.index
+        .iter()
+        .find(|item| item.id == product_id)
+        .map_or(product_id, |item| item.display.as_str())
+}
+
+fn print_price(row: &PriceRow) {
+    println!("{} ({})", row.name, row.product_id);
+    println!("  {:<14}{:>15}", "Instant Buy", fmt_price(row.instant_buy));
+    println!(
+        "  {:<14}{:>15}",
+        "Instant Sell",
+        fmt_price(row.instant_sell)
+    );
+    println!("  {:<14}{:>15}", "Spread", fmt_price(row.spread));
+    println!("  {:<14}{:>15}", "Spread %", fmt_pct(row.spread_pct));
+    match row.flip {
+        Some(f) => {
+            println!(
+                "  {:<14}{:>15}",
+                "Flip Profit",
+                fmt_price(f.profit_per_item)
+            );
+            println!("  {:<14}{:>15}", "Flip Margin", fmt_pct(f.margin * 100.0));
+        }
+        None => println!("  {:<14}{:>15}", "Flip", "not viable"),
+    }
+}
+
+/// Name column of the tables; longer names are cut to keep the columns lined up.
+const NAME_WIDTH: usize = 32;
+
+fn print_flips(rows: &[FlipRow]) {
+    println!(
+        "{:<NAME_WIDTH$}{:>15}{:>15}{:>15}{:>10}{:>8}",
+        "product", "buy order", "sell offer", "profit", "margin", "vol/wk"
+    );
+    for r in rows {
+        println!(
+            "{:<NAME_WIDTH$}{:>15}{:>15}{:>15}{:>10}{:>8}",
+            truncate(r.name, NAME_WIDTH - 1),
+            fmt_price(r.flip.order_price),
+            fmt_price(r.flip.offer_price),
+            fmt_price(r.flip.profit_per_item),
+            fmt_pct(r.flip.margin * 100.0),
+            fmt_compact(r.flip.buy_moving_week.min(r.flip.sell_moving_week)),
+        );
+    }
+}
+
+fn print_search(rows: &[PriceRow]) {
+    println!(
+        "{:<NAME_WIDTH$}{:>15}{:>15}{:>15}{:>10}",
+        "product", "buy", "sell", "profit", "margin"
+    );
+    for r in rows {
+        let (profit, margin) = match r.flip {
+            Some(f) => (fmt_price(f.profit_per_item), fmt_pct(f.margin * 100.0)),
+            None => ("—".to_string(), "—".to_string()),
+        };
+        println!(
+            "{:<NAME_WIDTH$}{:>15}{:>15}{:>15}{:>10}",
+            truncate(r.name, NAME_WIDTH - 1),
+            fmt_price(r.instant_buy),
+            fmt_price(r.instant_sell),
+            profit,
+            margin,
+        );
+    }
+}
@@ -1,31 +1,43 @@
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEvent, MouseEventKind,
     },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use ratatui::{Terminal, backend::CrosstermBackend};
-use std::io;
+use ratatui::{Terminal, backend::CrosstermBackend, layout::Position};
+use std::io::{self, Write};
+use std::time::Instant;
 use tokio::time::{self, Duration};
 use tokio::sync::mpsc;
 
-use crate::app::state::{App, View, SearchMode};
-use hypixel::models::skyblock::BazaarProduct;
-use crate::ui::views::{draw_detail, draw_search};
+use crate::app::book::FillCalc;
+use crate::app::columns::TableLayout;
+use crate::app::keymap::{Action, Context, Key, Resolved};
+use crate::app::state::{App, SearchMode, Update, View};
+use crate::ui::views::{
+    draw_alerts, draw_compare, draw_crafts, draw_detail, draw_help, draw_npc, draw_search,
+    draw_watchlist,
+};
 
 pub async fn run_app(app: &mut App) -> io::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if app.config.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     
     // channel for background updates from refresh task
     let (tx, rx) = mpsc::unbounded_channel();
     app.set_update_sender(tx);
-    
+    app.start_refresh();
+
     let res = run_loop(app, &mut terminal, rx).await;
+    app.stop_refresh();
 
     disable_raw_mode()?;
     execute!(
@@ -40,26 +52,52 @@ pub async fn run_app(app: &mut App) -> io::Result<()> {
 async fn run_loop(
     app: &mut App,
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
-    mut rx: mpsc::UnboundedReceiver<BazaarProduct>,
+    mut rx: mpsc::UnboundedReceiver<Update>,
 ) -> io::Result<()> {
     let mut tick = time::interval(Duration::from_millis(60));
-    let debounce = Duration::from_millis(120);
+    let mut mouse_captured = app.config.mouse;
 
     loop {
-        terminal.draw(|f| match app.view {
-            View::Search => draw_search(f, app),
-            View::Detail => draw_detail(f, app),
+        // A config reload can turn capture on or off.
+        if app.config.mouse != mouse_captured {
+            mouse_captured = app.config.mouse;
+            if mouse_captured {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            } else {
+                execute!(terminal.backend_mut(), DisableMouseCapture)?;
+            }
+        }
+
+        terminal.draw(|f| {
+            app.mouse.clear_areas();
+            match app.view {
+                View::Search => draw_search(f, app),
+                View::Detail => draw_detail(f, app),
+                View::Watchlist => draw_watchlist(f, app),
+                View::Alerts => draw_alerts(f, app),
+                View::Crafts => draw_crafts(f, app),
+                View::Npc => draw_npc(f, app),
+                View::Compare => draw_compare(f, app),
+            }
+            if app.help.is_some() {
+                draw_help(f, app);
+            }
         })?;
 
         tokio::select! {
             _ = tick.tick() => {
                 // Debounced filter on search input
                 if app.view == View::Search {
-                    app.maybe_apply_filter(debounce);
+                    app.maybe_apply_filter(app.config.search_debounce());
                 }
             }
-            Some(p) = rx.recv() => {
-                app.update_product(p);
+            Some(update) = rx.recv() => {
+                app.apply_update(update);
+                if app.take_bell() {
+                    let out = terminal.backend_mut();
+                    out.write_all(b"\x07")?;
+                    out.flush()?;
+                }
             }
             Ok(should_quit) = handle_event(app) => {
                 if should_quit { break; }
@@ -70,141 +108,514 @@ async fn run_loop(
 }
 
 async fn handle_event(app: &mut App) -> io::Result<bool> {
-    if event::poll(std::time::Duration::from_millis(16))?
-        && let Event::Key(key) = event::read()?
-            && key.kind == KeyEventKind::Press {
-                // Global quit
-                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-                    return Ok(true);
-                }
-
-                return Ok(match app.view {
-                    View::Search => handle_search_input(app, key),
-                    View::Detail => handle_detail_input(app, key),
-                });
-            }
+    if event::poll(std::time::Duration::from_millis(16))? {
+        return Ok(match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => handle_key(app, key),
+            Event::Mouse(ev) => handle_mouse(app, ev),
+            _ => false,
+        });
+    }
     Ok(false)
 }
 
-fn handle_search_input(app: &mut App, key: event::KeyEvent) -> bool {
-    match app.search.mode {
-        SearchMode::Insert => match key.code {
-            KeyCode::Esc => {
-                if app.search.input.is_empty() {
-                    return true; // quit
-                } else {
-                    app.on_delete();
-                }
+fn handle_key(app: &mut App, key: event::KeyEvent) -> bool {
+    let pressed = Key::from_event(key);
+    let context = match app.view {
+        View::Search => Context::Search,
+        View::Detail => Context::Detail,
+        View::Watchlist => Context::Watchlist,
+        View::Alerts => Context::Alerts,
+        View::Crafts => Context::Crafts,
+        View::Npc => Context::Npc,
+        View::Compare => Context::Compare,
+    };
+
+    if let Some(scroll) = app.help.as_mut() {
+        match app.keymap.resolve(context, pressed, false) {
+            Resolved::Action(Action::Quit) => return true,
+            Resolved::Action(Action::Up) => *scroll = scroll.saturating_sub(1),
+            Resolved::Action(Action::Down) => *scroll += 1,
+            Resolved::Action(Action::PageUp) => *scroll = scroll.saturating_sub(10),
+            Resolved::Action(Action::PageDown) => *scroll += 10,
+            Resolved::Action(Action::Top) => *scroll = 0,
+            Resolved::Pending => {}
+            _ => app.help = None,
+        }
+        return false;
+    }
+
+    // Popups read raw keys for their text fields; only quit gets past them.
+    if app.alerts.form.is_some() || app.detail.fill.is_some() || app.search.chooser.is_some() {
+        if app.keymap.is_bound(context, pressed, Action::Quit) {
+            return true;
+        }
+        match app.view {
+            View::Alerts => handle_alert_form(app, key, pressed),
+            View::Detail => handle_fill_calc(app, key, pressed),
+            View::Search => handle_column_chooser(app, key, pressed),
+            _ => {}
+        }
+        return false;
+    }
+
+    let typing = app.view == View::Search && app.search.mode == SearchMode::Insert;
+    let resolved = app.keymap.resolve(context, pressed, typing);
+    // A sequence that broke off in the search list was text after all.
+    for ch in app.keymap.take_broken().iter().filter_map(Key::as_char) {
+        if app.view == View::Search {
+            app.search.mode = SearchMode::Insert;
+            app.on_input(ch);
+        }
+    }
+    match resolved {
+        Resolved::Action(Action::Quit) => true,
+        Resolved::Action(Action::ReloadConfig) => {
+            app.reload_config();
+            false
+        }
+        Resolved::Action(Action::Help) => {
+            app.help = Some(0);
+            false
+        }
+        Resolved::Action(action) => dispatch(app, action),
+        Resolved::Pending => false,
+        Resolved::Unbound => {
+            if app.view == View::Search {
+                search_text(app, key);
             }
-            KeyCode::Up => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    app.jump_to_top();
-                } else {
-                    app.move_selection(-1);
+            false
+        }
+    }
+}
+
+/// Runs a view action; true means quit.
+fn dispatch(app: &mut App, action: Action) -> bool {
+    match app.view {
+        View::Search => search_action(app, action),
+        View::Detail => detail_action(app, action),
+        View::Watchlist => watchlist_action(app, action),
+        View::Alerts => alerts_action(app, action),
+        View::Crafts => crafts_action(app, action),
+        View::Npc => npc_action(app, action),
+        View::Compare => compare_action(app, action),
+    }
+}
+
+/// Two clicks on the same row within this long open it.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+fn handle_mouse(app: &mut App, ev: MouseEvent) -> bool {
+    if app.help.is_some() {
+        if matches!(ev.kind, MouseEventKind::Down(_)) {
+            app.help = None;
+        }
+        return false;
+    }
+    if app.alerts.form.is_some() || app.detail.fill.is_some() || app.search.chooser.is_some() {
+        return false;
+    }
+
+    let at = Position::new(ev.column, ev.row);
+    match ev.kind {
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let up = ev.kind == MouseEventKind::ScrollUp;
+            if app.view == View::Detail {
+                if app.mouse.chart.is_some_and(|r| r.contains(at)) {
+                    app.zoom_chart(up);
+                    return false;
                 }
-                app.search.mode = SearchMode::Navigate;
-            }
-            KeyCode::Down => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    app.jump_to_bottom();
-                } else {
-                    app.move_selection(1);
+                if !app.detail.show_depth {
+                    return false;
                 }
-                app.search.mode = SearchMode::Navigate;
-            }
-            KeyCode::PageUp => {
-                app.move_selection(-20);
-                app.search.mode = SearchMode::Navigate;
-            }
-            KeyCode::PageDown => {
-                app.move_selection(20);
-                app.search.mode = SearchMode::Navigate;
             }
-            KeyCode::Home => {
-                app.jump_to_top();
-                app.search.mode = SearchMode::Navigate;
-            }
-            KeyCode::End => {
-                app.jump_to_bottom();
-                app.search.mode = SearchMode::Navigate;
+            dispatch(app, if up { Action::Up } else { Action::Down })
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(&(_, action)) = app.mouse.buttons.iter().find(|(r, _)| r.contains(at)) {
+                return dispatch(app, action);
             }
-            KeyCode::Backspace => app.on_backspace(),
-            KeyCode::Delete => app.on_delete(),
-            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.search.sort_by_profit = !app.search.sort_by_profit;
-                app.recompute_filter();
-                app.status = if app.search.sort_by_profit { "Sorted by flip profit".into() } else { "Sorted by relevance".into() };
+            if let Some(&(_, category)) = app.mouse.tabs.iter().find(|(r, _)| r.contains(at)) {
+                app.set_category(category);
+                return false;
             }
-            KeyCode::Char(ch) => app.on_input(ch),
-            KeyCode::Enter => app.enter_detail(),
-            _ => {}
-        },
-        SearchMode::Navigate => match key.code {
-            KeyCode::Esc => {
-                if app.search.input.is_empty() {
-                    return true;
-                } else {
-                    app.on_delete();
-                }
+            if let Some(&(_, column)) = app.mouse.columns.iter().find(|(r, _)| r.contains(at)) {
+                app.change_table(|t| t.click_sort(column));
+                return false;
             }
-            KeyCode::Up => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    app.jump_to_top();
-                } else {
-                    app.move_selection(-1);
-                }
+            let Some((rows, offset)) = app.mouse.list.filter(|(r, _)| r.contains(at)) else {
+                return false;
+            };
+            let row = offset + (at.y - rows.y) as usize;
+            if !app.select_row(row) {
+                return false;
             }
-            KeyCode::Down => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    app.jump_to_bottom();
-                } else {
-                    app.move_selection(1);
-                }
+            let now = Instant::now();
+            let double = app
+                .mouse
+                .last_click
+                .is_some_and(|(last, t)| last == row && now.duration_since(t) < DOUBLE_CLICK);
+            app.mouse.last_click = (!double).then_some((row, now));
+            if !double {
+                return false;
             }
-            KeyCode::PageUp => app.move_selection(-20),
-            KeyCode::PageDown => app.move_selection(20),
-            KeyCode::Home => app.jump_to_top(),
-            KeyCode::End => app.jump_to_bottom(),
-            KeyCode::Backspace => {
-                app.search.mode = SearchMode::Insert;
-                app.on_backspace();
+            let open = if app.view == View::Alerts {
+                Action::EditAlert
+            } else {
+                Action::Open
+            };
+            dispatch(app, open)
+        }
+        _ => false,
+    }
+}
+
+/// How far a movement action moves a list selection.
+fn step(action: Action) -> Option<isize> {
+    const FAR: isize = i32::MAX as isize;
+    match action {
+        Action::Up => Some(-1),
+        Action::Down => Some(1),
+        Action::PageUp => Some(-20),
+        Action::PageDown => Some(20),
+        Action::Top => Some(-FAR),
+        Action::Bottom => Some(FAR),
+        _ => None,
+    }
+}
+
+fn search_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back => {
+            if app.search.input.is_empty() {
+                return true; // quit
             }
-            KeyCode::Delete => {
-                app.search.mode = SearchMode::Insert;
-                app.on_delete();
+            app.on_delete();
+        }
+        Action::Top => {
+            app.jump_to_top();
+            app.search.mode = SearchMode::Navigate;
+        }
+        Action::Bottom => {
+            app.jump_to_bottom();
+            app.search.mode = SearchMode::Navigate;
+        }
+        Action::Up | Action::Down | Action::PageUp | Action::PageDown => {
+            if let Some(delta) = step(action) {
+                app.move_selection(delta);
             }
-            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.search.sort_by_profit = !app.search.sort_by_profit;
-                app.recompute_filter();
-                app.status = if app.search.sort_by_profit { "Sorted by flip profit".into() } else { "Sorted by relevance".into() };
+            app.search.mode = SearchMode::Navigate;
+        }
+        Action::FocusSearch => app.search.mode = SearchMode::Insert,
+        Action::CycleSort => app.change_table(TableLayout::cycle_sort),
+        Action::ReverseSort => app.change_table(TableLayout::reverse_sort),
+        Action::Columns => app.search.chooser = Some(0),
+        Action::PrevCategory => app.cycle_category(-1),
+        Action::NextCategory => app.cycle_category(1),
+        Action::ToggleStar => {
+            if let Some(id) = app.selected_product_id().map(str::to_owned) {
+                app.toggle_star(&id);
             }
-            KeyCode::Char(ch) => {
-                app.search.mode = SearchMode::Insert;
-                app.on_input(ch);
+        }
+        Action::ToggleMark => {
+            if let Some(id) = app.selected_product_id().map(str::to_owned) {
+                app.toggle_mark(&id);
             }
-            KeyCode::Enter => app.enter_detail(),
-            _ => {}
-        },
+        }
+        Action::Compare => app.open_compare(),
+        Action::NextView => app.view = View::Watchlist,
+        Action::Open => app.enter_detail(),
+        _ => {}
     }
     false
 }
 
-fn handle_detail_input(app: &mut App, key: event::KeyEvent) -> bool {
+fn handle_column_chooser(app: &mut App, key: event::KeyEvent, pressed: Key) {
+    let Some(cursor) = app.search.chooser else {
+        return;
+    };
+    let order = app.search.table.chooser_order();
+    let Some(&(column, _)) = order.get(cursor) else {
+        app.search.chooser = None;
+        return;
+    };
+    // Follow the column as it moves, so repeated [ or ] keep shifting it.
+    let follow = |app: &mut App| {
+        let order = app.search.table.chooser_order();
+        app.search.chooser = order.iter().position(|&(c, _)| c == column);
+    };
+    // No text here, so the search view's own move and sort keys work too.
+    let bound = |action| app.keymap.is_bound(Context::Search, pressed, action);
+    let (up, down, sort) = (
+        bound(Action::Up),
+        bound(Action::Down),
+        bound(Action::CycleSort),
+    );
     match key.code {
-        KeyCode::Esc | KeyCode::Char('b') => app.exit_detail(),
-        KeyCode::Char('p') => {
+        _ if key.code == KeyCode::Esc || bound(Action::Columns) => {
+            app.search.chooser = None;
+        }
+        _ if up => app.search.chooser = Some(cursor.saturating_sub(1)),
+        _ if down => app.search.chooser = Some((cursor + 1).min(order.len() - 1)),
+        _ if sort => app.change_table(|t| t.click_sort(column)),
+        KeyCode::Up => app.search.chooser = Some(cursor.saturating_sub(1)),
+        KeyCode::Down => app.search.chooser = Some((cursor + 1).min(order.len() - 1)),
+        KeyCode::Char(' ') | KeyCode::Enter => {
+            app.change_table(|t| t.toggle(column));
+            follow(app);
+        }
+        KeyCode::Char('[') | KeyCode::Left => {
+            app.change_table(|t| t.shift(column, -1));
+            follow(app);
+        }
+        KeyCode::Char(']') | KeyCode::Right => {
+            app.change_table(|t| t.shift(column, 1));
+            follow(app);
+        }
+        KeyCode::Char('s') => app.change_table(|t| t.click_sort(column)),
+        _ => {}
+    }
+}
+
+/// Keys no binding claimed edit the query, switching back to typing.
+fn search_text(app: &mut App, key: event::KeyEvent) {
+    match key.code {
+        KeyCode::Backspace => {
+            app.search.mode = SearchMode::Insert;
+            app.on_backspace();
+        }
+        KeyCode::Delete => {
+            app.search.mode = SearchMode::Insert;
+            app.on_delete();
+        }
+        KeyCode::Char(ch)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            app.search.mode = SearchMode::Insert;
+            app.on_input(ch);
+        }
+        _ => {}
+    }
+}
+
+fn detail_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back => app.exit_detail(),
+        Action::TogglePercent => {
             app.detail.show_percent = !app.detail.show_percent;
             app.status = if app.detail.show_percent { "Chart: % mode".into() } else { "Chart: absolute mode".into() };
         }
-        KeyCode::Char('m') => {
+        Action::ToggleSma => {
             app.detail.show_sma = !app.detail.show_sma;
             app.status = if app.detail.show_sma { "SMA: on".into() } else { "SMA: off".into() };
         }
-        KeyCode::Char('g') => {
+        Action::ToggleMidline => {
             app.detail.show_midline = !app.detail.show_midline;
             app.status = if app.detail.show_midline { "Midline: on".into() } else { "Midline: off".into() };
         }
-        KeyCode::Char('r') => app.manual_refresh(),
+        Action::ToggleEma => {
+            app.detail.show_ema = !app.detail.show_ema;
+            app.status = if app.detail.show_ema {
+                "EMA: on".into()
+            } else {
+                "EMA: off".into()
+            };
+        }
+        Action::ToggleBollinger => {
+            app.detail.show_bollinger = !app.detail.show_bollinger;
+            app.status = if app.detail.show_bollinger {
+                "Bollinger: on".into()
+            } else {
+                "Bollinger: off".into()
+            };
+        }
+        Action::ToggleTwap => {
+            app.detail.show_twap = !app.detail.show_twap;
+            app.status = if app.detail.show_twap {
+                "TWAP: on".into()
+            } else {
+                "TWAP: off".into()
+            };
+        }
+        Action::ToggleEnvelope => {
+            app.detail.show_envelope = !app.detail.show_envelope;
+            app.status = if app.detail.show_envelope {
+                "Envelope: on".into()
+            } else {
+                "Envelope: off".into()
+            };
+        }
+        Action::ToggleSpread => {
+            app.detail.show_spread = !app.detail.show_spread;
+            app.status = if app.detail.show_spread {
+                "Spread pane: on".into()
+            } else {
+                "Spread pane: off".into()
+            };
+        }
+        Action::PrevRange | Action::NextRange => app.step_chart_range(action == Action::NextRange),
+        Action::ZoomIn | Action::ZoomOut => app.zoom_chart(action == Action::ZoomIn),
+        Action::PanLeft | Action::PanRight => app.pan_chart(action == Action::PanLeft),
+        Action::ToggleCandles => {
+            app.detail.show_candles = !app.detail.show_candles;
+            app.status = if app.detail.show_candles {
+                "Candles: on".into()
+            } else {
+                "Candles: off".into()
+            };
+        }
+        Action::CandleInterval => app.cycle_candle_interval(),
+        Action::ToggleStar => {
+            if let Some(id) = app.detail.product_id.clone() {
+                app.toggle_star(&id);
+            }
+        }
+        Action::ToggleDepth => {
+            app.detail.show_depth = !app.detail.show_depth;
+            app.status = if app.detail.show_depth {
+                "Depth: on".into()
+            } else {
+                "Depth: off".into()
+            };
+        }
+        Action::Top if app.detail.show_depth => app.detail.depth_scroll = 0,
+        Action::Up | Action::Down | Action::PageUp | Action::PageDown if app.detail.show_depth => {
+            app.detail.depth_scroll += step(action).unwrap_or(0);
+        }
+        Action::FillCalc => app.detail.fill = Some(FillCalc::default()),
+        Action::NewAlert => {
+            let id = app.detail.product_id.clone();
+            app.exit_detail();
+            app.new_alert(id.as_deref());
+        }
+        Action::Refresh => app.manual_refresh(),
+        _ => {}
+    }
+    false
+}
+
+fn handle_fill_calc(app: &mut App, key: event::KeyEvent, pressed: Key) {
+    let close = key.code == KeyCode::Esc
+        || app
+            .keymap
+            .is_bound(Context::Detail, pressed, Action::FillCalc);
+    let Some(calc) = app.detail.fill.as_mut() else {
+        return;
+    };
+    match key.code {
+        _ if close => app.detail.fill = None,
+        KeyCode::Tab | KeyCode::Left | KeyCode::Right => calc.side = calc.side.flip(),
+        KeyCode::Backspace => calc.backspace(),
+        KeyCode::Char(ch) => calc.input(ch),
+        _ => {}
+    }
+}
+
+fn watchlist_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back => app.view = View::Search,
+        Action::NextView => app.view = View::Alerts,
+        Action::Open => app.enter_watch_detail(),
+        Action::ToggleStar => {
+            if let Some(id) = app.selected_watch_id().map(str::to_owned) {
+                app.toggle_star(&id);
+            }
+        }
+        _ => {
+            if let Some(delta) = step(action) {
+                app.move_watch_selection(delta);
+            }
+        }
+    }
+    false
+}
+
+fn handle_alert_form(app: &mut App, key: event::KeyEvent, pressed: Key) {
+    // Plain letters are typed into the form, so only other back keys close it.
+    let cancel = key.code == KeyCode::Esc
+        || (!pressed.is_plain_char()
+            && app.keymap.is_bound(Context::Alerts, pressed, Action::Back));
+    let Some(form) = app.alerts.form.as_mut() else {
+        return;
+    };
+    match key.code {
+        _ if cancel => app.alerts.form = None,
+        KeyCode::Enter => app.submit_alert_form(),
+        KeyCode::Tab | KeyCode::Down => form.next_field(1),
+        KeyCode::BackTab | KeyCode::Up => form.next_field(-1),
+        KeyCode::Left => form.cycle(-1),
+        KeyCode::Right => form.cycle(1),
+        KeyCode::Backspace => form.backspace(),
+        KeyCode::Char(ch) => form.input(ch),
+        _ => {}
+    }
+}
+
+fn alerts_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back => app.view = View::Search,
+        Action::NextView => app.view = View::Crafts,
+        Action::NewAlert => app.new_alert(None),
+        Action::EditAlert => app.edit_selected_alert(),
+        Action::DeleteAlert => app.delete_selected_alert(),
+        _ => {
+            if let Some(delta) = step(action) {
+                app.move_alert_selection(delta);
+            }
+        }
+    }
+    false
+}
+
+fn crafts_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back if app.crafts.part.is_some() => app.crafts.part = None,
+        Action::Back => app.view = View::Search,
+        Action::NextView => {
+            app.crafts.part = None;
+            app.view = View::Npc;
+        }
+        Action::Open => app.open_craft(),
+        Action::CycleSort => app.toggle_craft_rank(),
+        Action::InputPricing => {
+            app.crafts.inputs = app.crafts.inputs.flip();
+            app.check_craft_breakdown();
+        }
+        Action::OutputPricing => {
+            app.crafts.output = app.crafts.output.flip();
+            app.check_craft_breakdown();
+        }
+        _ => {
+            if let Some(delta) = step(action) {
+                app.move_craft_selection(delta);
+            }
+        }
+    }
+    false
+}
+
+fn npc_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back | Action::NextView => app.view = View::Search,
+        Action::Open => app.enter_npc_detail(),
+        _ => {
+            if let Some(delta) = step(action) {
+                app.move_npc_selection(delta);
+            }
+        }
+    }
+    false
+}
+
+fn compare_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Back => app.exit_compare(),
+        Action::Open => app.open_compare_detail(),
+        Action::Left => app.move_compare_selection(-1),
+        Action::Right => app.move_compare_selection(1),
+        Action::ToggleMark => app.unmark_selected(),
+        Action::Refresh => app.manual_refresh(),
         _ => {}
     }
     false
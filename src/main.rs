@@ -1,23 +1,97 @@
 mod app;
+mod cli;
 mod events;
 mod ui;
 mod util;
 
-use crate::app::state::App;
-use hypixel::HypixelClient;
+use crate::app::alerts::AlertBook;
+use crate::app::columns::TableLayout;
+use crate::app::config::Overrides;
+use crate::app::history::HistoryStore;
+use crate::app::source::{BazaarSource, Recorder, Recording, Replay, live_client};
+use crate::app::state::{App, BazaarData};
+use crate::app::watchlist::Watchlist;
+use crate::cli::Command;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 use std::error::Error;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Save every bazaar snapshot fetched to this directory
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Play back snapshots saved with --record instead of calling the API
+    #[arg(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// Print JSON instead of a table (subcommands only)
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(flatten)]
+    overrides: Overrides,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    // Keyless endpoint; clones share this client's connection pool.
-    let client = HypixelClient::builder()
-        .timeout(Duration::from_secs(10))
-        .retry_on_rate_limit(2)
-        .build();
-
-    let initial = client.skyblock_bazaar().await?;
-    let mut app = App::new(client, initial);
+    let cli = Cli::parse();
+    // Clap can't make a flag require a subcommand, so check by hand.
+    if cli.json && cli.command.is_none() {
+        Cli::command()
+            .error(
+                ErrorKind::MissingSubcommand,
+                "--json needs one of the price, flips or search subcommands",
+            )
+            .exit();
+    }
+    let replaying = cli.replay.is_some();
+    let config = cli.overrides.load()?;
+
+    let source: Arc<dyn BazaarSource> = match (cli.replay, cli.record) {
+        (Some(dir), _) => Arc::new(Replay::open(&dir)?),
+        (None, record) => {
+            let client = live_client(config.request_timeout());
+            match record {
+                Some(dir) => Arc::new(Recording::new(client, Recorder::new(dir)?)),
+                None => Arc::new(client),
+            }
+        }
+    };
+
+    let initial = source.fetch().await?;
+    if let Some(command) = cli.command {
+        return cli::run(command, cli.json, &BazaarData::new(initial, &config));
+    }
+
+    let mut app = App::new(source, initial, config);
+    app.set_overrides(cli.overrides);
+    // A replay is somebody else's past; keep it out of the real history.
+    if !replaying && let Some(store) = HistoryStore::open_default() {
+        app.set_history_store(store);
+    }
+
+    // A broken file falls back to defaults rather than blocking startup.
+    let mut unreadable = Vec::new();
+    let (watchlist, error) = Watchlist::load_default();
+    unreadable.extend(error.map(|e| format!("watchlist.toml: {e}")));
+    app.set_watchlist(watchlist);
+    let (layout, error) = TableLayout::load_default();
+    unreadable.extend(error.map(|e| format!("columns.toml: {e}")));
+    app.set_table_layout(layout);
+    let (alerts, error) = AlertBook::load_default();
+    unreadable.extend(error.map(|e| format!("alerts.toml: {e}")));
+    app.set_alerts(alerts);
+    if !unreadable.is_empty() {
+        app.status = format!("Ignored {}", unreadable.join("; "));
+    }
 
     events::run::run_app(&mut app).await?;
     Ok(())
@@ -0,0 +1,135 @@
+use crate::app::chart::Candle;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Widget};
+
+/// OHLC candles drawn with block characters, one column per candle and
+/// two price steps per row: full blocks for the body, half blocks where
+/// it ends mid-cell, and a thin line for the wicks.
+pub struct CandleChart<'a> {
+    candles: &'a [Candle],
+    block: Option<Block<'a>>,
+    bounds: [f64; 2],
+    y_labels: [String; 3],
+    x_labels: [String; 2],
+}
+
+impl<'a> CandleChart<'a> {
+    pub fn new(candles: &'a [Candle], bounds: [f64; 2]) -> Self {
+        Self {
+            candles,
+            block: None,
+            bounds,
+            y_labels: Default::default(),
+            x_labels: Default::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Labels for the bottom, middle and top of the price axis.
+    pub fn y_labels(mut self, labels: [String; 3]) -> Self {
+        self.y_labels = labels;
+        self
+    }
+
+    /// Labels for the first and last candle shown.
+    pub fn x_labels(mut self, labels: [String; 2]) -> Self {
+        self.x_labels = labels;
+        self
+    }
+
+    /// How many candles fit in `width` columns, leaving room for the axis.
+    pub fn capacity(width: u16, label_width: u16) -> usize {
+        width.saturating_sub(label_width + 3) as usize
+    }
+}
+
+impl Widget for CandleChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        let label_width = self
+            .y_labels
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        if inner.height < 3 || inner.width <= label_width + 1 {
+            return;
+        }
+        let dim = Style::default().fg(Color::DarkGray);
+
+        // Price axis on the left, time axis along the bottom.
+        let plot = Rect {
+            x: inner.x + label_width + 1,
+            y: inner.y,
+            width: inner.width - label_width - 1,
+            height: inner.height - 1,
+        };
+        let rows = [plot.bottom() - 1, plot.y + plot.height / 2, plot.y];
+        for (label, y) in self.y_labels.iter().zip(rows) {
+            buf.set_string(
+                inner.x,
+                y,
+                format!("{label:>w$}", w = label_width as usize),
+                dim,
+            );
+        }
+        for y in plot.top()..plot.bottom() {
+            buf.set_string(plot.x - 1, y, "│", dim);
+        }
+        let axis_y = plot.bottom();
+        buf.set_string(plot.x - 1, axis_y, "└", dim);
+        buf.set_string(plot.x, axis_y, &self.x_labels[0], dim);
+        let last = &self.x_labels[1];
+        let last_x = plot.right().saturating_sub(last.chars().count() as u16);
+        if last_x > plot.x + self.x_labels[0].chars().count() as u16 {
+            buf.set_string(last_x, axis_y, last, dim);
+        }
+
+        let [lo, hi] = self.bounds;
+        let steps = plot.height as f64 * 2.0 - 1.0;
+        let level = |v: f64| {
+            let t = if hi > lo { (v - lo) / (hi - lo) } else { 0.5 };
+            (t.clamp(0.0, 1.0) * steps).round() as i64
+        };
+
+        // The newest candles, right-aligned.
+        let shown = &self.candles[self.candles.len().saturating_sub(plot.width as usize)..];
+        let x0 = plot.right() - shown.len() as u16;
+        for (i, c) in shown.iter().enumerate() {
+            let color = if c.rising() { Color::Green } else { Color::Red };
+            let (wick_lo, wick_hi) = (level(c.low), level(c.high));
+            let (body_lo, body_hi) = (level(c.open.min(c.close)), level(c.open.max(c.close)));
+            let x = x0 + i as u16;
+            for row in 0..plot.height {
+                // Price steps covered by this cell, counted from the bottom.
+                let lower = row as i64 * 2;
+                let upper = lower + 1;
+                let in_body = |s: i64| (body_lo..=body_hi).contains(&s);
+                let symbol = match (in_body(lower), in_body(upper)) {
+                    (true, true) => "█",
+                    (false, true) => "▀",
+                    (true, false) => "▄",
+                    _ if lower <= wick_hi && upper >= wick_lo => "│",
+                    _ => continue,
+                };
+                buf.set_string(
+                    x,
+                    plot.bottom() - 1 - row,
+                    symbol,
+                    Style::default().fg(color),
+                );
+            }
+        }
+    }
+}
@@ -1,32 +1,141 @@
-use crate::app::state::{App, SearchMode, prices};
-use crate::util::{fmt_compact, fmt_count, fmt_pct, fmt_price};
+use crate::app::alerts::{AlertForm, FormField};
+use crate::app::book::{DepthLevel, FillCalc, Side, cumulative, fill, mid_price, step_series};
+use crate::app::categories;
+use crate::app::chart::{
+    CANDLE_INTERVALS, CHART_ZOOM_STEP, ChartWindow, auto_interval, candles, downsample,
+};
+use crate::app::columns::Column;
+use crate::app::crafts::{self, CraftQuote, CraftRank, Pricing};
+use crate::app::history::Sample;
+use crate::app::indicators::{DEPTH_BAND_PCT, pressure};
+use crate::app::items::Rarity;
+use crate::app::keymap::{Action, Context, Keymap};
+use crate::app::overlays::{self, Series};
+use crate::app::state::{App, MAX_COMPARE, SearchMode, View, prices};
+use crate::ui::candles::CandleChart;
+use crate::util::{
+    fmt_axis_time, fmt_compact, fmt_count, fmt_pct, fmt_price, fmt_time, now_ms, truncate,
+};
 use hypixel::models::skyblock::{BazaarProduct, BazaarQuickStatus};
 use hypixel::util::market::BazaarFlip;
 use ratatui::{
     prelude::*,
     symbols,
     widgets::{
-        Axis, Block, Borders, Cell, Chart, Dataset, GraphType, List, ListItem, ListState,
-        Paragraph, Row, Table, Wrap,
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, List, ListItem, ListState,
+        Paragraph, Row, Table, TableState, Wrap,
     },
 };
 
-/// Draws the search view, consisting of input, results, and status bar.
+/// Line colours of the compare view, one per marked product.
+const COMPARE_COLORS: [Color; MAX_COMPARE] =
+    [Color::Cyan, Color::Yellow, Color::Magenta, Color::LightBlue];
+
+/// Draws the marked products side by side above their overlaid % change.
+pub fn draw_compare(frame: &mut Frame, app: &mut App) {
+    // Header, one row per column and six quick-status rows, plus borders.
+    let table_height = 1 + Column::ALL.len() as u16 + 1 + 6 + 2;
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(table_height), // Side-by-side stats
+            Constraint::Min(6),               // % change chart
+            Constraint::Length(1),            // Status bar
+        ])
+        .split(frame.area());
+
+    draw_compare_table(frame, app, layout[0]);
+    draw_compare_chart(frame, app, layout[1]);
+    draw_status_bar(frame, app, layout[2]);
+}
+
+/// Draws the search view, consisting of input, category tabs, results, and status bar.
 pub fn draw_search(frame: &mut Frame, app: &mut App) {
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),      // Search input
+            Constraint::Length(1),      // Category tabs
             Constraint::Min(1),         // Results
             Constraint::Length(1),      // Status bar
         ])
         .split(frame.area());
 
     draw_search_input(frame, app, layout[0]);
-    draw_search_results(frame, app, layout[1]);
+    draw_category_tabs(frame, app, layout[1]);
+    draw_search_results(frame, app, layout[2]);
+    draw_status_bar(frame, app, layout[3]);
+    if let Some(cursor) = app.search.chooser {
+        draw_column_chooser(frame, app, cursor, layout[2]);
+    }
+}
+
+/// Draws the starred products with the same columns as search.
+pub fn draw_watchlist(frame: &mut Frame, app: &mut App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Starred products
+            Constraint::Length(1), // Status bar
+        ])
+        .split(frame.area());
+
+    draw_watchlist_table(frame, app, layout[0]);
+    draw_status_bar(frame, app, layout[1]);
+}
+
+/// Draws alert rules, the log of fired alerts, and the rule form when open.
+pub fn draw_alerts(frame: &mut Frame, app: &mut App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(40), // Rules
+            Constraint::Min(1),         // Log
+            Constraint::Length(1),      // Status bar
+        ])
+        .split(frame.area());
+
+    draw_alert_rules(frame, app, layout[0]);
+    draw_alert_log(frame, app, layout[1]);
+    draw_status_bar(frame, app, layout[2]);
+
+    if let Some(form) = &app.alerts.form {
+        draw_alert_form(frame, form, &app.keymap, frame.area());
+    }
+}
+
+/// Draws recipes ranked by craft profit over the selected one's breakdown.
+pub fn draw_crafts(frame: &mut Frame, app: &mut App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(60), // Recipes
+            Constraint::Min(1),         // Breakdown
+            Constraint::Length(1),      // Status bar
+        ])
+        .split(frame.area());
+
+    let quotes = app.craft_quotes();
+    let row = app.craft_row(&quotes);
+    draw_craft_list(frame, app, &quotes, row, layout[0]);
+    draw_craft_parts(frame, app, quotes.get(row), layout[1]);
     draw_status_bar(frame, app, layout[2]);
 }
 
+/// Draws products the bazaar sells below their NPC price.
+pub fn draw_npc(frame: &mut Frame, app: &mut App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Flips
+            Constraint::Length(1), // Status bar
+        ])
+        .split(frame.area());
+
+    draw_npc_flips(frame, app, layout[0]);
+    draw_status_bar(frame, app, layout[1]);
+}
+
 /// Draws the detail view for a selected product.
 pub fn draw_detail(frame: &mut Frame, app: &mut App) {
     let layout = Layout::default()
@@ -40,41 +149,334 @@ pub fn draw_detail(frame: &mut Frame, app: &mut App) {
 
     draw_detail_header(frame, app, layout[0]);
 
+    if app.detail.show_depth {
+        let below_header = layout[1].union(layout[2]);
+        draw_depth(frame, app, below_header);
+    } else {
+        draw_detail_panes(frame, app, layout[1], layout[2]);
+        app.mouse.chart = Some(layout[2]);
+    }
+
+    if let (Some(calc), Some(p)) = (&app.detail.fill, app.current_product()) {
+        draw_fill_calc(
+            frame,
+            calc,
+            p,
+            app.config.tax_rate,
+            &app.keymap,
+            frame.area(),
+        );
+    }
+}
+
+/// Every action for the current view with its keys, over whatever is drawn.
+pub fn draw_help(frame: &mut Frame, app: &mut App) {
+    let (context, view) = match app.view {
+        View::Search => (Context::Search, "Search"),
+        View::Detail => (Context::Detail, "Product"),
+        View::Watchlist => (Context::Watchlist, "Watchlist"),
+        View::Alerts => (Context::Alerts, "Alerts"),
+        View::Crafts => (Context::Crafts, "Crafts"),
+        View::Npc => (Context::Npc, "NPC flips"),
+        View::Compare => (Context::Compare, "Compare"),
+    };
+    let entries = app.keymap.entries(context);
+    let area = frame.area();
+    let popup = centered_rect(70, (entries.len() as u16 + 3).min(area.height), area);
+    frame.render_widget(Clear, popup);
+
+    let visible = popup.height.saturating_sub(3) as usize;
+    let max_scroll = entries.len().saturating_sub(visible);
+    let scroll = app.help.unwrap_or(0).min(max_scroll);
+    app.help = Some(scroll);
+
+    let key_width = entries
+        .iter()
+        .map(|(_, keys)| keys.join(", ").chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    let rows = entries.iter().skip(scroll).map(|(action, keys)| {
+        Row::new(vec![
+            Cell::from(keys.join(", ")).style(Style::default().fg(Color::Yellow)),
+            Cell::from(action.describe(context)),
+        ])
+    });
+    let more = if max_scroll > 0 {
+        "↑/↓ scroll • "
+    } else {
+        ""
+    };
+    let table = Table::new(
+        rows,
+        [Constraint::Length(key_width + 2), Constraint::Min(10)],
+    )
+    .header(
+        Row::new(vec!["Key", "Action"]).style(
+            Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(
+        Block::default()
+            .title(format!("Keys — {view} ({more}any other key closes)"))
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(table, popup);
+}
+
+fn draw_detail_panes(frame: &mut Frame, app: &App, top: Rect, bottom: Rect) {
     let middle = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
-        .split(layout[1]);
+        .split(top);
 
     if let Some(p) = app.current_product() {
         let flip = app.detail.product_id.as_deref().and_then(|id| app.flip(id));
+        let stats = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(middle[0]);
         match p.quick_status.as_ref() {
-            Some(q) => draw_quick_status(frame, q, flip, middle[0]),
+            Some(q) => draw_quick_status(frame, q, flip, stats[0]),
             None => frame.render_widget(
                 Paragraph::new("No quick status reported")
                     .block(Block::default().title("Quick Status").borders(Borders::ALL)),
-                middle[0],
+                stats[0],
             ),
         }
+        draw_pressure(frame, p, stats[1]);
         draw_orders(frame, p, middle[1]);
-        draw_history_chart(frame, layout[2], app);
+        draw_history_chart(frame, bottom, app);
     } else {
         let msg = Paragraph::new("No product selected")
             .block(Block::default().borders(Borders::ALL));
-        frame.render_widget(msg, layout[1]);
+        frame.render_widget(msg, top);
+    }
+}
+
+fn draw_fill_calc(
+    frame: &mut Frame,
+    calc: &FillCalc,
+    product: &BazaarProduct,
+    tax_rate: f64,
+    keymap: &Keymap,
+    area: Rect,
+) {
+    let popup = centered_rect(50, 11, area);
+    frame.render_widget(Clear, popup);
+
+    let label = |s: &str| Span::styled(format!("{s:<13}"), Style::default().fg(Color::Gray));
+    let mut lines = vec![
+        Line::from(vec![
+            label("Side"),
+            Span::raw(format!("◂ {} ▸", calc.side.label())),
+        ]),
+        Line::from(vec![
+            label("Quantity"),
+            Span::styled(
+                format!("{} ", calc.quantity),
+                Style::default().add_modifier(Modifier::REVERSED),
+            ),
+        ]),
+        Line::raw(""),
+    ];
+
+    match calc.parsed() {
+        None => lines.push(Line::from(Span::styled(
+            "Type an amount, e.g. 50000 or 50k",
+            Style::default().fg(Color::DarkGray),
+        ))),
+        Some(qty) => {
+            let f = fill(product, calc.side, qty, tax_rate);
+            let total_label = match calc.side {
+                Side::Buy => "Total cost",
+                Side::Sell => "Total (net)",
+            };
+            let row = |name: &str, value: String| Line::from(vec![label(name), Span::raw(value)]);
+            lines.push(row(
+                "Avg price",
+                f.avg_price().map_or("-".into(), fmt_price),
+            ));
+            lines.push(row(total_label, fmt_price(f.total())));
+            if f.tax > 0.0 {
+                lines.push(row("Tax", fmt_price(f.tax)));
+            }
+            lines.push(row(
+                "Worst level",
+                match f.worst_price {
+                    Some(p) => format!(
+                        "{} ({} level{})",
+                        fmt_price(p),
+                        f.levels_used,
+                        if f.levels_used == 1 { "" } else { "s" }
+                    ),
+                    None => "-".into(),
+                },
+            ));
+            lines.push(if f.complete() {
+                Line::from(Span::styled(
+                    "Book absorbs the full amount",
+                    Style::default().fg(Color::Green),
+                ))
+            } else {
+                Line::from(Span::styled(
+                    format!(
+                        "Listed book only holds {} of {}",
+                        fmt_count(f.filled),
+                        fmt_count(qty)
+                    ),
+                    Style::default().fg(Color::Red),
+                ))
+            });
+        }
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(
+                    "Fill cost (Tab side • {} close)",
+                    or_bound("Esc", keymap.label(Context::Detail, Action::FillCalc))
+                ))
+                .borders(Borders::ALL),
+        ),
+        popup,
+    );
+}
+
+/// "All" plus every category, scrolled so the active tab is on screen.
+fn draw_category_tabs(frame: &mut Frame, app: &mut App, area: Rect) {
+    let tabs: Vec<Option<&'static str>> = std::iter::once(None)
+        .chain(categories::names().map(Some))
+        .collect();
+    let label = |tab: Option<&str>| format!(" {} ", tab.unwrap_or("All"));
+    let active = tabs
+        .iter()
+        .position(|&t| t == app.search.category)
+        .unwrap_or(0);
+
+    // Drop tabs off the left until the active one fits.
+    let mut first = 0;
+    while first < active
+        && tabs[first..=active]
+            .iter()
+            .map(|&t| label(t).len() as u16 + 1)
+            .sum::<u16>()
+            + 2
+            > area.width
+    {
+        first += 1;
+    }
+
+    let mut spans = vec![Span::styled(
+        if first > 0 { "‹ " } else { "  " },
+        Style::default().fg(Color::DarkGray),
+    )];
+    let mut x = area.x + 2;
+    for (i, &tab) in tabs.iter().enumerate().skip(first) {
+        let text = label(tab);
+        let width = text.len() as u16;
+        let style = if i == active {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        app.mouse
+            .tabs
+            .push((Rect::new(x, area.y, width, 1).intersection(area), tab));
+        spans.push(Span::styled(text, style));
+        spans.push(Span::raw(" "));
+        x = x.saturating_add(width + 1);
     }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Every column with a tick for the visible ones, in table order.
+fn draw_column_chooser(frame: &mut Frame, app: &App, cursor: usize, area: Rect) {
+    let order = app.search.table.chooser_order();
+    let popup = centered_rect(40, (order.len() as u16 + 4).min(area.height), area);
+    frame.render_widget(Clear, popup);
+
+    let mut lines: Vec<Line> = order
+        .iter()
+        .enumerate()
+        .map(|(i, &(column, shown))| {
+            let mark = if shown { "[x]" } else { "[ ]" };
+            let sorted = match app.search.table.sort() {
+                Some(c) if c == column && app.search.table.descending() => " ▼",
+                Some(c) if c == column => " ▲",
+                _ => "",
+            };
+            let style = if i == cursor {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::REVERSED)
+            } else if shown {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(Span::styled(
+                format!(" {mark} {}{sorted}", column.label()),
+                style,
+            ))
+        })
+        .collect();
+    lines.push(Line::raw(""));
+    let select = app
+        .keymap
+        .hint(Context::Search, &[Action::Up, Action::Down], "select");
+    let sort = or_bound("s", app.keymap.label(Context::Search, Action::CycleSort));
+    let hints: Vec<String> = select
+        .into_iter()
+        .chain([
+            "Space show/hide".to_string(),
+            "[ ] move".to_string(),
+            format!("{sort} sort"),
+        ])
+        .collect();
+    lines.push(Line::from(Span::styled(
+        format!(" {}", hints.join(" • ")),
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(
+                    "Columns ({} close)",
+                    or_bound("Esc", app.keymap.label(Context::Search, Action::Columns))
+                ))
+                .borders(Borders::ALL),
+        ),
+        popup,
+    );
 }
 
 fn draw_search_input(frame: &mut Frame, app: &App, area: Rect) {
     let input_line = if app.search.input.is_empty() {
         Line::from(vec![Span::styled(
-            "Type to search…",
+            "Type to search…  filters like margin>5% vol>100k buy<10k narrow it down",
             Style::default().fg(Color::DarkGray),
         )])
     } else {
         Line::from(Span::raw(app.search.input.as_str()))
     };
 
-    let input_block = Block::default().title("Search").borders(Borders::ALL);
+    let mut title = vec![Span::raw("Search")];
+    if let Some(err) = &app.search.query_error {
+        title.push(Span::styled(
+            format!(" ⚠ {err} "),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let input_block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL);
     let input = Paragraph::new(input_line)
         .block(input_block.clone())
         .wrap(Wrap { trim: true });
@@ -89,67 +491,136 @@ fn draw_search_input(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
+// Borders (2) + highlight symbol ("▸ ", 2) around the name and numeric columns.
+const ROW_CHROME_WIDTH: u16 = 2 + 2;
+const MIN_NAME_WIDTH: usize = 16;
+
+fn name_width(columns: &[Column], area: Rect) -> usize {
+    let fixed: u16 = columns.iter().map(|c| c.width()).sum();
+    area.width
+        .saturating_sub(fixed + ROW_CHROME_WIDTH)
+        .max(MIN_NAME_WIDTH as u16) as usize
+}
+
+/// Column captions, aligned with [`product_row`]. In a `sortable` table the
+/// sorted one carries an arrow and each caption's rect is kept so a click can
+/// sort by it.
+fn columns_header(app: &mut App, name_width: usize, area: Rect, sortable: bool) -> Line<'static> {
+    let table = &app.search.table;
+    let mut spans = vec![Span::raw(" ".repeat(name_width + 2))];
+    let mut x = area.x + 1 + name_width as u16 + 2;
+    let y = area.bottom().saturating_sub(1);
+    for &column in table.columns() {
+        let width = column.width();
+        let sorted = sortable && table.sort() == Some(column);
+        let caption = match (sorted, table.descending()) {
+            (true, true) => format!("{} ▼", column.label()),
+            (true, false) => format!("{} ▲", column.label()),
+            (false, _) => column.label().to_string(),
+        };
+        let color = if sorted {
+            Color::Yellow
+        } else {
+            Color::DarkGray
+        };
+        spans.push(Span::styled(
+            format!("{caption:>w$}", w = width as usize),
+            Style::default().fg(color),
+        ));
+        if sortable {
+            app.mouse
+                .columns
+                .push((Rect::new(x, y, width, 1).intersection(area), column));
+        }
+        x = x.saturating_add(width);
+    }
+    Line::from(spans).left_aligned()
+}
+
+/// One numeric cell, right-aligned to its column.
+fn column_cell(
+    column: Column,
+    product: &BazaarProduct,
+    flip: Option<&BazaarFlip>,
+) -> Span<'static> {
+    let width = column.width() as usize;
+    let Some(v) = column.value(product, flip) else {
+        return Span::styled(
+            format!("{:>width$}", "—"),
+            Style::default().fg(Color::DarkGray),
+        );
+    };
+    let (text, color) = match column {
+        Column::Buy => (fmt_price(v), Color::Green),
+        Column::Sell => (fmt_price(v), Color::Red),
+        Column::Spread => (fmt_price(v), Color::White),
+        Column::Profit => (fmt_price(v), Color::Yellow),
+        Column::SpreadPct | Column::Margin => (fmt_pct(v), spread_color(v)),
+        Column::Volume => (fmt_compact(v as i64), Color::DarkGray),
+        Column::Imbalance => (format!("{v:+.2}"), pressure_color(v, 0.2)),
+        Column::Flow => (format!("{v:.2}×"), pressure_color(v - 1.0, 0.25)),
+        Column::AvgBid | Column::AvgAsk | Column::Depth => (fmt_compact(v as i64), Color::Gray),
+    };
+    Span::styled(format!("{text:>width$}"), Style::default().fg(color))
+}
+
+/// One product as a row of the chosen columns; starred names get a `★`
+/// and names marked for comparing a `◆`.
+fn product_row(
+    app: &App,
+    id: &str,
+    display: &str,
+    rarity: Option<Rarity>,
+    name_width: usize,
+) -> ListItem<'static> {
+    let mut name = if app.watch.list.contains(id) {
+        format!("★ {display}")
+    } else {
+        display.to_string()
+    };
+    if app.compare.marked.iter().any(|m| m == id) {
+        name.insert_str(0, "◆ ");
+    }
+
+    let Some(product) = app.data.products.get(id).filter(|p| prices(p).is_some()) else {
+        return ListItem::new(Line::from(Span::styled(
+            name,
+            Style::default().fg(Color::DarkGray),
+        )));
+    };
+
+    let flip = app.flip(id);
+    let mut spans = vec![Span::styled(
+        format!("{:<name_width$}", truncate(&name, name_width)),
+        Style::default()
+            .fg(rarity.map_or(Color::White, rarity_color))
+            .add_modifier(Modifier::BOLD),
+    )];
+    spans.extend(
+        app.search
+            .table
+            .columns()
+            .iter()
+            .map(|&c| column_cell(c, product, flip)),
+    );
+    ListItem::new(Line::from(spans))
+}
+
 fn draw_search_results(frame: &mut Frame, app: &mut App, area: Rect) {
-    // Fixed-width numeric columns + borders (2) + highlight symbol ("▸ ", 2).
-    const FIXED_COLS_WIDTH: u16 = 15 + 15 + 15 + 10 + 8 + 2 + 2;
-    const MIN_NAME_WIDTH: usize = 16;
-    let name_width = area
-        .width
-        .saturating_sub(FIXED_COLS_WIDTH)
-        .max(MIN_NAME_WIDTH as u16) as usize;
+    let name_width = name_width(app.search.table.columns(), area);
+    let header = columns_header(app, name_width, area, true);
+    let sort = match app.search.table.sort() {
+        Some(c) if app.search.table.descending() => format!("by {} ▼", c.label()),
+        Some(c) => format!("by {} ▲", c.label()),
+        None => "by relevance".to_string(),
+    };
 
     let items: Vec<ListItem> = app
         .search.filtered_indices
         .iter()
         .map(|i| {
             let item = &app.data.index[*i];
-            if let Some(px) = app.data.products.get(&item.id).and_then(prices) {
-                let flip = app.flip(&item.id);
-                let (profit, margin, volume) = match flip {
-                    Some(f) => (
-                        fmt_price(f.profit_per_item),
-                        fmt_pct(f.margin * 100.0),
-                        fmt_compact(f.buy_moving_week.min(f.sell_moving_week)),
-                    ),
-                    None => ("—".to_string(), "—".to_string(), "—".to_string()),
-                };
-                let margin_color = flip.map_or(Color::DarkGray, |f| spread_color(f.margin * 100.0));
-
-                let line = Line::from(vec![
-                    Span::styled(
-                        format!("{:<name_width$}", truncate(&item.display, name_width)),
-                        Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(
-                        format!("{:>15}", fmt_price(px.instant_buy)),
-                        Style::default().fg(Color::Green),
-                    ),
-                    Span::styled(
-                        format!("{:>15}", fmt_price(px.instant_sell)),
-                        Style::default().fg(Color::Red),
-                    ),
-                    Span::styled(
-                        format!("{profit:>15}"),
-                        Style::default().fg(if flip.is_some() {
-                            Color::Yellow
-                        } else {
-                            Color::DarkGray
-                        }),
-                    ),
-                    Span::styled(format!("{margin:>10}"), Style::default().fg(margin_color)),
-                    Span::styled(
-                        format!("{volume:>8}"),
-                        Style::default().fg(Color::DarkGray),
-                    ),
-                ]);
-                ListItem::new(line)
-            } else {
-                let styled = Line::from(Span::styled(
-                    item.display.clone(),
-                    Style::default().fg(Color::DarkGray),
-                ));
-                ListItem::new(styled)
-            }
+            product_row(app, &item.id, &item.display, item.rarity, name_width)
         })
         .collect();
 
@@ -167,56 +638,693 @@ fn draw_search_results(frame: &mut Frame, app: &mut App, area: Rect) {
                         format!("({} results, ", app.search.filtered_indices.len()),
                         Style::default().fg(Color::Gray),
                     ),
+                    Span::styled(sort, Style::default().fg(Color::Yellow)),
+                    Span::styled(")", Style::default().fg(Color::Gray)),
+                ]))
+                .title_bottom(header)
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        )
+        .highlight_symbol("▸ ");
+        
+    frame.render_stateful_widget(list, area, &mut list_state);
+    app.mouse.list = Some((area.inner(Margin::new(1, 1)), list_state.offset()));
+}
+
+fn draw_watchlist_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let name_width = name_width(app.search.table.columns(), area);
+    let header = columns_header(app, name_width, area, false);
+    let starred = app.watch.list.products();
+
+    let block = Block::default()
+        .title(Line::from(vec![
+            Span::styled("Watchlist ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("({} starred)", starred.len()),
+                Style::default().fg(Color::Gray),
+            ),
+        ]))
+        .title_bottom(header)
+        .borders(Borders::ALL);
+
+    if starred.is_empty() {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                format!(
+                    "Nothing starred yet.{}",
+                    press_hint(
+                        app,
+                        &[
+                            (Context::Search, Action::ToggleStar, "on a search result"),
+                            (Context::Detail, Action::ToggleStar, "in a product"),
+                        ],
+                    )
+                ),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block),
+            area,
+        );
+        return;
+    }
+
+    let items: Vec<ListItem> = starred
+        .iter()
+        .map(|id| {
+            let rarity = app.index_item(id).and_then(|item| item.rarity);
+            product_row(app, id, app.display_name(id), rarity, name_width)
+        })
+        .collect();
+
+    let mut list_state = ListState::default();
+    list_state.select(Some(app.watch.selected_index));
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        )
+        .highlight_symbol("▸ ");
+
+    frame.render_stateful_widget(list, area, &mut list_state);
+    app.mouse.list = Some((area.inner(Margin::new(1, 1)), list_state.offset()));
+}
+
+fn draw_alert_rules(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rules = app.alerts.book.rules();
+    let block = Block::default()
+        .title(Line::from(vec![
+            Span::styled(
+                "Alert rules ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("({})", rules.len()),
+                Style::default().fg(Color::Gray),
+            ),
+        ]))
+        .borders(Borders::ALL);
+
+    if rules.is_empty() {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                format!(
+                    "No rules yet.{}",
+                    press_hint(
+                        app,
+                        &[
+                            (Context::Alerts, Action::NewAlert, "to add one"),
+                            (Context::Detail, Action::NewAlert, "from a product"),
+                        ],
+                    )
+                ),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block),
+            area,
+        );
+        return;
+    }
+
+    let items: Vec<ListItem> = rules.iter().map(|r| ListItem::new(r.describe())).collect();
+    let mut list_state = ListState::default();
+    list_state.select(Some(app.alerts.selected_index));
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        )
+        .highlight_symbol("▸ ");
+    frame.render_stateful_widget(list, area, &mut list_state);
+    app.mouse.list = Some((area.inner(Margin::new(1, 1)), list_state.offset()));
+}
+
+// cost, revenue, profit, margin, crafts/h, profit/h + borders and highlight symbol.
+const CRAFT_COLS_WIDTH: u16 = 14 + 14 + 14 + 9 + 9 + 14 + 2 + 2;
+
+fn draw_craft_list(
+    frame: &mut Frame,
+    app: &mut App,
+    quotes: &[CraftQuote],
+    row: usize,
+    area: Rect,
+) {
+    let name_width = area
+        .width
+        .saturating_sub(CRAFT_COLS_WIDTH)
+        .max(MIN_NAME_WIDTH as u16) as usize;
+    let dim = Style::default().fg(Color::DarkGray);
+    let items: Vec<ListItem> = quotes
+        .iter()
+        .map(|q| {
+            let recipe = &crafts::recipes()[q.recipe];
+            let mut name = app.display_name(&recipe.output).to_string();
+            if recipe.count > 1 {
+                name = format!("{name} ×{}", recipe.count);
+            }
+            let profit_color = if q.profit() > 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<name_width$}", truncate(&name, name_width)),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("{:>14}", fmt_price(q.cost))),
+                Span::raw(format!("{:>14}", fmt_price(q.revenue))),
+                Span::styled(
+                    format!("{:>14}", fmt_price(q.profit())),
+                    Style::default().fg(profit_color),
+                ),
+                Span::styled(
+                    format!("{:>9}", fmt_pct(q.margin() * 100.0)),
+                    Style::default().fg(spread_color(q.margin() * 100.0)),
+                ),
+                Span::styled(format!("{:>9}", format!("{:.1}", q.crafts_per_hour)), dim),
+                Span::styled(
+                    format!("{:>14}", fmt_price(q.profit_per_hour())),
+                    Style::default().fg(profit_color),
+                ),
+            ]))
+        })
+        .collect();
+
+    let inputs = match app.crafts.inputs {
+        Pricing::Instant => "instant buy",
+        Pricing::Order => "buy order",
+    };
+    let output = match app.crafts.output {
+        Pricing::Instant => "instant sell",
+        Pricing::Order => "sell order",
+    };
+    let rank = match app.crafts.rank {
+        CraftRank::PerCraft => "by profit/craft",
+        CraftRank::PerHour => "by profit/hour",
+    };
+    let header = Line::from(Span::styled(
+        format!(
+            "{:<w$}{:>14}{:>14}{:>14}{:>9}{:>9}{:>14}",
+            "",
+            "cost",
+            "revenue",
+            "profit",
+            "margin",
+            "crafts/h",
+            "profit/h",
+            w = name_width + 2
+        ),
+        dim,
+    ));
+
+    let mut list_state = ListState::default();
+    if !quotes.is_empty() {
+        list_state.select(Some(row));
+    }
+    let drilled = app.crafts.part.is_some();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(Line::from(vec![
+                    Span::styled("Crafts ", Style::default().add_modifier(Modifier::BOLD)),
                     Span::styled(
-                        if app.search.sort_by_profit {
-                            "by flip profit"
-                        } else {
-                            "by relevance"
-                        },
-                        Style::default().fg(Color::Yellow),
+                        format!("({} recipes, buy {inputs}, sell {output}, ", quotes.len()),
+                        Style::default().fg(Color::Gray),
                     ),
+                    Span::styled(rank, Style::default().fg(Color::Yellow)),
                     Span::styled(")", Style::default().fg(Color::Gray)),
                 ]))
-                .title_bottom(
-                    Line::from(Span::styled(
-                        format!(
-                            "{:<name_width$}{:>15}{:>15}{:>15}{:>10}{:>8}",
-                            "", "buy", "sell", "profit", "margin", "vol/wk"
-                        ),
-                        Style::default().fg(Color::DarkGray),
-                    ))
-                    .left_aligned(),
-                )
+                .title_bottom(header.left_aligned())
                 .borders(Borders::ALL),
         )
+        .highlight_style(if drilled {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        })
+        .highlight_symbol("▸ ");
+    frame.render_stateful_widget(list, area, &mut list_state);
+    if !drilled {
+        app.mouse.list = Some((area.inner(Margin::new(1, 1)), list_state.offset()));
+    }
+}
+
+/// Each input at the chosen buy price, then the output at the sell price.
+fn draw_craft_parts(frame: &mut Frame, app: &mut App, quote: Option<&CraftQuote>, area: Rect) {
+    let Some(recipe) = quote.and_then(|q| crafts::recipes().get(q.recipe)) else {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                "No recipe has every part listed on the bazaar right now.",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(Block::default().title("Breakdown").borders(Borders::ALL)),
+            area,
+        );
+        return;
+    };
+
+    let parts = app.craft_parts();
+    let rows: Vec<Row> = parts
+        .iter()
+        .enumerate()
+        .map(|(i, &(id, amount))| {
+            let is_output = i + 1 == parts.len();
+            let product = app.data.products.get(id);
+            let unit = product.and_then(|p| {
+                if is_output {
+                    app.crafts.output.sell_price(p)
+                } else {
+                    app.crafts.inputs.buy_price(p)
+                }
+            });
+            let volume = product
+                .and_then(|p| Column::Volume.value(p, None))
+                .map_or("—".to_string(), |v| fmt_compact(v as i64));
+            let (role, color) = if is_output {
+                ("out", Color::Green)
+            } else {
+                ("in", Color::Red)
+            };
+            Row::new(vec![
+                Cell::from(role).style(Style::default().fg(color)),
+                Cell::from(app.display_name(id).to_string()),
+                Cell::from(fmt_count(amount as i64)),
+                Cell::from(unit.map_or("—".into(), fmt_price)),
+                Cell::from(unit.map_or("—".into(), |u| fmt_price(u * amount as f64))),
+                Cell::from(volume).style(Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+
+    let hint = app
+        .keymap
+        .label(Context::Crafts, Action::Open)
+        .map(|k| format!(" ({k} opens the book)"))
+        .unwrap_or_default();
+    let title = format!("{}{hint}", app.display_name(&recipe.output));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Min(16),
+            Constraint::Length(8),
+            Constraint::Length(14),
+            Constraint::Length(16),
+            Constraint::Length(8),
+        ],
+    )
+    .header(
+        Row::new(vec!["", "Product", "Qty", "Unit", "Subtotal", "Vol/wk"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().title(title).borders(Borders::ALL))
+    .row_highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::REVERSED),
+    )
+    .highlight_symbol("▸ ");
+
+    let mut state = TableState::default();
+    state.select(app.crafts.part);
+    frame.render_stateful_widget(table, area, &mut state);
+    if app.crafts.part.is_some() {
+        // Rows start under the header line.
+        let rows = area.inner(Margin::new(1, 1));
+        let rows = Rect::new(
+            rows.x,
+            rows.y + 1,
+            rows.width,
+            rows.height.saturating_sub(1),
+        );
+        app.mouse.list = Some((rows, state.offset()));
+    }
+}
+
+// npc, instant buy, profit, buy order, profit, units, attainable + borders and highlight symbol.
+const NPC_COLS_WIDTH: u16 = 12 * 5 + 10 + 14 + 2 + 2;
+
+fn draw_npc_flips(frame: &mut Frame, app: &mut App, area: Rect) {
+    let flips = app.npc_flips();
+    let name_width = area
+        .width
+        .saturating_sub(NPC_COLS_WIDTH)
+        .max(MIN_NAME_WIDTH as u16) as usize;
+    let profit = |v: f64| {
+        let color = if v > 0.0 {
+            Color::Green
+        } else {
+            Color::DarkGray
+        };
+        Span::styled(format!("{:>12}", fmt_price(v)), Style::default().fg(color))
+    };
+    let items: Vec<ListItem> = flips
+        .iter()
+        .map(|f| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{:<name_width$}",
+                        truncate(app.display_name(&f.product_id), name_width)
+                    ),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{:>12}", fmt_price(f.npc_price)),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!("{:>12}", fmt_price(f.instant_buy))),
+                profit(f.instant_profit()),
+                Span::raw(format!("{:>12}", fmt_price(f.buy_order))),
+                profit(f.order_profit()),
+                Span::styled(
+                    format!("{:>10}", fmt_compact(f.available)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:>14}", fmt_price(f.attainable)),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]))
+        })
+        .collect();
+
+    let header = Line::from(Span::styled(
+        format!(
+            "{:<w$}{:>12}{:>12}{:>12}{:>12}{:>12}{:>10}{:>14}",
+            "",
+            "npc",
+            "insta buy",
+            "profit",
+            "buy order",
+            "profit",
+            "units",
+            "attainable",
+            w = name_width + 2
+        ),
+        Style::default().fg(Color::DarkGray),
+    ));
+    let block = Block::default()
+        .title(Line::from(vec![
+            Span::styled("NPC flips ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("({} below the merchant price)", flips.len()),
+                Style::default().fg(Color::Gray),
+            ),
+        ]))
+        .title_bottom(header.left_aligned())
+        .borders(Borders::ALL);
+
+    if flips.is_empty() {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                "Nothing on the bazaar is cheaper than an NPC pays for it right now.",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block),
+            area,
+        );
+        return;
+    }
+
+    let mut list_state = ListState::default();
+    list_state.select(Some(app.npc.selected_index.min(flips.len() - 1)));
+    let list = List::new(items)
+        .block(block)
         .highlight_style(
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD | Modifier::REVERSED),
         )
         .highlight_symbol("▸ ");
-        
     frame.render_stateful_widget(list, area, &mut list_state);
+    app.mouse.list = Some((area.inner(Margin::new(1, 1)), list_state.offset()));
+}
+
+fn draw_alert_log(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .alerts
+        .book
+        .log()
+        .map(|a| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{}  ", fmt_time(a.at)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{} ", app.display_name(&a.product_id)),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(a.message.clone(), Style::default().fg(Color::Yellow)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(
+                "Fired",
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(list, area);
+}
+
+fn draw_alert_form(frame: &mut Frame, form: &AlertForm, keymap: &Keymap, area: Rect) {
+    let popup = centered_rect(60, 9, area);
+    frame.render_widget(Clear, popup);
+
+    let field = |which: FormField, label: &str, value: String| {
+        let focused = form.field == which;
+        Line::from(vec![
+            Span::styled(
+                format!("{label:<11}"),
+                Style::default().fg(if focused { Color::Yellow } else { Color::Gray }),
+            ),
+            Span::styled(
+                value,
+                if focused {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                },
+            ),
+        ])
+    };
+
+    let product = if form.product.is_empty() && form.field != FormField::Product {
+        "any product".to_string()
+    } else {
+        form.product.clone()
+    };
+    let mut lines = vec![
+        field(FormField::Product, "Product", product),
+        field(
+            FormField::Metric,
+            "Metric",
+            format!("◂ {} ▸", form.metric.label()),
+        ),
+        field(
+            FormField::Comparison,
+            "When",
+            format!("◂ {} ▸", form.comparison.label()),
+        ),
+        field(FormField::Threshold, "Threshold", form.threshold.clone()),
+        Line::raw(""),
+    ];
+    lines.push(match &form.error {
+        Some(e) => Line::from(Span::styled(e.clone(), Style::default().fg(Color::Red))),
+        None => Line::from(Span::styled(
+            format!(
+                "Tab next field • ←/→ change • Enter save • {} cancel",
+                or_bound("Esc", keymap.text_label(Context::Alerts, Action::Back))
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+    });
+
+    let title = if form.editing.is_some() {
+        "Edit alert"
+    } else {
+        "New alert"
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL)),
+        popup,
+    );
+}
+
+/// A `width`% wide, `height`-row tall box in the middle of `area`.
+fn centered_rect(width_pct: u16, height: u16, area: Rect) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    let [cell] = Layout::horizontal([Constraint::Percentage(width_pct)])
+        .flex(layout::Flex::Center)
+        .areas(row);
+    cell
+}
+
+type Hints = &'static [(&'static [Action], &'static str)];
+
+const SEARCH_HINTS: Hints = &[
+    (&[Action::Help], "help"),
+    (&[Action::Back], "quit"),
+    (&[Action::Open], "detail"),
+    (&[Action::Up, Action::Down], "navigate"),
+    (&[Action::FocusSearch], "search"),
+    (&[Action::CycleSort], "sort"),
+    (&[Action::Columns], "columns"),
+    (&[Action::PrevCategory, Action::NextCategory], "category"),
+    (&[Action::ToggleStar], "star"),
+    (&[Action::ToggleMark], "mark"),
+    (&[Action::Compare], "compare"),
+    (&[Action::NextView], "watchlist"),
+];
+
+const CRAFTS_HINTS: Hints = &[
+    (&[Action::Help], "help"),
+    (&[Action::Back], "back"),
+    (&[Action::Open], "drill in"),
+    (&[Action::CycleSort], "rank"),
+    (&[Action::InputPricing], "inputs"),
+    (&[Action::OutputPricing], "output"),
+    (&[Action::NextView], "npc flips"),
+];
+
+const NPC_HINTS: Hints = &[
+    (&[Action::Help], "help"),
+    (&[Action::Back, Action::NextView], "search"),
+    (&[Action::Open], "detail"),
+    (&[Action::Up, Action::Down], "navigate"),
+];
+
+const COMPARE_HINTS: Hints = &[
+    (&[Action::Help], "help"),
+    (&[Action::Back], "search"),
+    (&[Action::Left, Action::Right], "select"),
+    (&[Action::Open], "detail"),
+    (&[Action::ToggleMark], "drop"),
+];
+
+const WATCHLIST_HINTS: Hints = &[
+    (&[Action::Help], "help"),
+    (&[Action::Back], "search"),
+    (&[Action::NextView], "alerts"),
+    (&[Action::Open], "detail"),
+    (&[Action::Up, Action::Down], "navigate"),
+    (&[Action::ToggleStar], "unstar"),
+];
+
+const ALERTS_HINTS: Hints = &[
+    (&[Action::Help], "help"),
+    (&[Action::Back], "search"),
+    (&[Action::NextView], "crafts"),
+    (&[Action::NewAlert], "add"),
+    (&[Action::EditAlert], "edit"),
+    (&[Action::DeleteAlert], "delete"),
+];
+
+/// Hint text for the active keymap; unbound actions drop out.
+fn hint_line(app: &App, context: Context, hints: Hints) -> String {
+    hints
+        .iter()
+        .filter_map(|(actions, text)| app.keymap.hint(context, actions, text))
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+/// `fixed`, plus the bound key when it is a different one: `Esc/c`.
+fn or_bound(fixed: &str, bound: Option<String>) -> String {
+    match bound {
+        Some(key) if key != fixed => format!("{fixed}/{key}"),
+        _ => fixed.to_string(),
+    }
+}
+
+/// `" Press Ctrl+F on a search result or f in a product."` from whichever
+/// of the actions are bound; empty when none are.
+fn press_hint(app: &App, parts: &[(Context, Action, &str)]) -> String {
+    let parts: Vec<String> = parts
+        .iter()
+        .filter_map(|&(context, action, text)| {
+            app.keymap
+                .label(context, action)
+                .map(|key| format!("{key} {text}"))
+        })
+        .collect();
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" Press {}.", parts.join(" or "))
+    }
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let mode = if app.search.mode == SearchMode::Insert { "Insert" } else { "Navigate" };
-    let hints = "Esc quit • Enter detail • ↑/↓ navigate • Ctrl+S sort";
-    let status_line = Line::from(vec![
-        Span::styled(app.status.clone(), Style::default().fg(Color::Gray)),
+    let hints = match app.view {
+        View::Watchlist => hint_line(app, Context::Watchlist, WATCHLIST_HINTS),
+        View::Alerts => hint_line(app, Context::Alerts, ALERTS_HINTS),
+        View::Crafts => hint_line(app, Context::Crafts, CRAFTS_HINTS),
+        View::Npc => hint_line(app, Context::Npc, NPC_HINTS),
+        View::Compare => hint_line(app, Context::Compare, COMPARE_HINTS),
+        _ => hint_line(app, Context::Search, SEARCH_HINTS),
+    };
+    let status_style = if app.alert_flashing() {
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+    let mut spans = vec![
+        Span::styled(app.status.clone(), status_style),
         Span::raw("   "),
         Span::styled(hints, Style::default().fg(Color::DarkGray)),
         Span::raw("   |  Data "),
         Span::styled(age_label(app.data.last_updated), Style::default().fg(Color::DarkGray)),
-        Span::raw("   |  Mode: "),
-        Span::styled(mode, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-    ]);
+    ];
+    if app.view == View::Search {
+        let mode = if app.search.mode == SearchMode::Insert {
+            "Insert"
+        } else {
+            "Navigate"
+        };
+        spans.push(Span::raw("   |  Mode: "));
+        spans.push(Span::styled(
+            mode,
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+    let status_line = Line::from(spans);
     
     let status = Paragraph::new(status_line).wrap(Wrap { trim: true });
     frame.render_widget(status, area);
 }
 
-fn draw_detail_header(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_detail_header(frame: &mut Frame, app: &mut App, area: Rect) {
     let Some(id) = &app.detail.product_id else {
         frame.render_widget(
             Paragraph::new("Detail").style(Style::default().fg(Color::Yellow)),
@@ -225,23 +1333,92 @@ fn draw_detail_header(frame: &mut Frame, app: &App, area: Rect) {
         return;
     };
 
-    let toggle = |on: bool, label: &str| {
-        Span::styled(
-            format!("{label} "),
+    let key = |action: Action, text: &str| {
+        app.keymap
+            .label(Context::Detail, action)
+            .map(|k| format!("{k}={text} "))
+            .unwrap_or_default()
+    };
+    let toggle = |on: bool, action: Action, text: &str| {
+        let span = Span::styled(
+            key(action, text),
             Style::default().fg(if on { Color::Yellow } else { Color::DarkGray }),
-        )
+        );
+        (span, Some(action))
+    };
+    let plain = |action: Action, text: &str| {
+        let span = Span::styled(key(action, text), Style::default().fg(Color::DarkGray));
+        (span, Some(action))
     };
 
-    let header = Line::from(vec![
-        Span::styled(
-            format!("{id}   "),
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+    let item = app.index_item(id);
+    let name = item.map_or(id.as_str(), |item| item.display.as_str());
+    let rarity = item.and_then(|item| item.rarity);
+    let mut parts = vec![
+        (
+            Span::styled(
+                format!("{name} "),
+                Style::default()
+                    .fg(rarity.map_or(Color::Yellow, rarity_color))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            None,
         ),
-        Span::styled("b=back  r=refresh   ", Style::default().fg(Color::DarkGray)),
-        toggle(app.detail.show_percent, "p=%"),
-        toggle(app.detail.show_sma, "m=sma"),
-        toggle(app.detail.show_midline, "g=mean"),
-    ]);
+        (
+            Span::styled(
+                match rarity {
+                    Some(r) => format!("{} {id}   ", r.label()),
+                    None => format!("{id}   "),
+                },
+                Style::default().fg(Color::DarkGray),
+            ),
+            None,
+        ),
+        plain(Action::Back, "back"),
+        plain(Action::Refresh, "refresh"),
+        (Span::raw("  "), None),
+        toggle(app.detail.show_percent, Action::TogglePercent, "%"),
+        toggle(app.detail.show_sma, Action::ToggleSma, "sma"),
+        toggle(app.detail.show_midline, Action::ToggleMidline, "mean"),
+        toggle(app.detail.show_ema, Action::ToggleEma, "ema"),
+        toggle(app.detail.show_bollinger, Action::ToggleBollinger, "bb"),
+        toggle(app.detail.show_twap, Action::ToggleTwap, "twap"),
+        toggle(app.detail.show_envelope, Action::ToggleEnvelope, "env"),
+        toggle(app.detail.show_spread, Action::ToggleSpread, "spread"),
+        toggle(app.detail.show_candles, Action::ToggleCandles, "ohlc"),
+        toggle(app.detail.show_depth, Action::ToggleDepth, "depth"),
+        toggle(app.watch.list.contains(id), Action::ToggleStar, "★"),
+        toggle(app.detail.fill.is_some(), Action::FillCalc, "fill"),
+        plain(Action::NewAlert, "alert"),
+        plain(Action::Help, "help"),
+    ];
+    if app.alert_flashing() {
+        parts.push((Span::raw("   "), None));
+        parts.push((
+            Span::styled(
+                app.status.clone(),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            None,
+        ));
+    }
+
+    // Every labelled key is also a button.
+    let mut x = area.x;
+    for (span, action) in &parts {
+        let width = span.width() as u16;
+        if let Some(action) = action
+            && width > 0
+        {
+            let button = Rect::new(x, area.y, width - 1, 1).intersection(area);
+            app.mouse.buttons.push((button, *action));
+        }
+        x = x.saturating_add(width);
+    }
+    let header = Line::from(parts.into_iter().map(|(span, _)| span).collect::<Vec<_>>());
     frame.render_widget(Paragraph::new(header), area);
 }
 
@@ -325,6 +1502,55 @@ fn draw_quick_status(
     frame.render_widget(table, area);
 }
 
+/// Derived book indicators next to the raw quick-status counts.
+fn draw_pressure(frame: &mut Frame, product: &BazaarProduct, area: Rect) {
+    let p = pressure(product);
+    let dim = Style::default().fg(Color::DarkGray);
+    let value = |v: Option<f64>, text: fn(f64) -> String, color: fn(f64) -> Color| match v {
+        Some(v) => Cell::from(text(v)).style(Style::default().fg(color(v))),
+        None => Cell::from("—").style(dim),
+    };
+    let rows = vec![
+        Row::new(vec![
+            Cell::from("Imbalance"),
+            value(
+                p.imbalance,
+                |v| format!("{v:+.2}"),
+                |v| pressure_color(v, 0.2),
+            ),
+        ]),
+        Row::new(vec![
+            Cell::from("Flow B/S"),
+            value(
+                p.flow,
+                |v| format!("{v:.2}×"),
+                |v| pressure_color(v - 1.0, 0.25),
+            ),
+        ]),
+        Row::new(vec![
+            Cell::from("Bid Size"),
+            value(p.avg_bid, |v| fmt_compact(v as i64), |_| Color::Gray),
+        ]),
+        Row::new(vec![
+            Cell::from("Ask Size"),
+            value(p.avg_ask, |v| fmt_compact(v as i64), |_| Color::Gray),
+        ]),
+        Row::new(vec![Cell::from(""), Cell::from("")]),
+        Row::new(vec![
+            Cell::from(format!("Bids ±{DEPTH_BAND_PCT}%")),
+            Cell::from(fmt_count(p.bid_depth)).style(Style::default().fg(Color::Red)),
+        ]),
+        Row::new(vec![
+            Cell::from(format!("Asks ±{DEPTH_BAND_PCT}%")),
+            Cell::from(fmt_count(p.ask_depth)).style(Style::default().fg(Color::Green)),
+        ]),
+    ];
+
+    let table = Table::new(rows, [Constraint::Length(11), Constraint::Min(8)])
+        .block(Block::default().title("Pressure").borders(Borders::ALL));
+    frame.render_widget(table, area);
+}
+
 fn draw_orders(frame: &mut Frame, p: &BazaarProduct, area: Rect) {
     // buy_summary is the ask side, sell_summary the bid side.
     let buys = p.buy_summary.iter().take(5).map(|o| {
@@ -372,17 +1598,207 @@ fn draw_orders(frame: &mut Frame, p: &BazaarProduct, area: Rect) {
 
     let sell_table = Table::new(sells, widths).header(header).block(
         Block::default()
-            .title("Instant Sell — best bids")
+            .title("Instant Sell — best bids")
+            .borders(Borders::ALL),
+    );
+
+    frame.render_widget(sell_table, chunks[1]);
+}
+
+/// Every book level as a ladder, asks over bids, beside a cumulative depth chart.
+fn draw_depth(frame: &mut Frame, app: &mut App, area: Rect) {
+    let Some(p) = app.current_product() else {
+        return;
+    };
+    // buy_summary is the ask side, sell_summary the bid side.
+    let asks = cumulative(&p.buy_summary);
+    let bids = cumulative(&p.sell_summary);
+    let mid = mid_price(p);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(area);
+
+    let level_row = |l: &DepthLevel, color: Color| {
+        Row::new(vec![
+            colored_price(l.price, color),
+            Cell::from(fmt_count(l.amount)),
+            Cell::from(fmt_count(l.cumulative)),
+            Cell::from(fmt_count(l.orders)),
+        ])
+    };
+    let mut rows: Vec<Row> = asks
+        .iter()
+        .rev()
+        .map(|l| level_row(l, Color::Green))
+        .collect();
+    rows.push(
+        Row::new(vec![
+            Cell::from(mid.map_or_else(|| "—".to_string(), fmt_price)),
+            Cell::from("mid"),
+        ])
+        .style(
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        ),
+    );
+    rows.extend(bids.iter().map(|l| level_row(l, Color::Red)));
+
+    // Borders and the header row leave this many rows for levels.
+    let visible = chunks[0].height.saturating_sub(3) as isize;
+    let max_offset = (rows.len() as isize - visible).max(0);
+    let centred = (asks.len() as isize - visible / 2).clamp(0, max_offset);
+    let offset = (centred + app.detail.depth_scroll).clamp(0, max_offset);
+    app.detail.depth_scroll = offset - centred;
+
+    let header = Row::new(vec!["Price", "Amt", "Cum", "#"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let ladder = Table::new(
+        rows.into_iter().skip(offset as usize),
+        [
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(13),
+            Constraint::Length(6),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(format!(
+                "Order book — {} asks / {} bids{}",
+                asks.len(),
+                bids.len(),
+                app.keymap
+                    .hint(Context::Detail, &[Action::Up, Action::Down], "scroll")
+                    .map(|h| format!(" ({h})"))
+                    .unwrap_or_default()
+            ))
             .borders(Borders::ALL),
     );
+    frame.render_widget(ladder, chunks[0]);
 
-    frame.render_widget(sell_table, chunks[1]);
+    draw_depth_chart(frame, chunks[1], &asks, &bids, mid);
+}
+
+fn draw_depth_chart(
+    frame: &mut Frame,
+    area: Rect,
+    asks: &[DepthLevel],
+    bids: &[DepthLevel],
+    mid: Option<f64>,
+) {
+    let block = Block::default().title("Depth").borders(Borders::ALL);
+    let Some(mid) = mid else {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                "needs orders on both sides",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block),
+            area,
+        );
+        return;
+    };
+
+    let ask_pts = step_series(asks);
+    let bid_pts = step_series(bids);
+    let max_cum = asks
+        .last()
+        .map_or(0, |l| l.cumulative)
+        .max(bids.last().map_or(0, |l| l.cumulative))
+        .max(1) as f64;
+
+    // Symmetric around mid, but a stray far-out order should not squash the
+    // levels that matter, so the span is capped at ±50%.
+    let reach = asks
+        .last()
+        .map_or(0.0, |l| l.price - mid)
+        .max(bids.last().map_or(0.0, |l| mid - l.price))
+        .min(mid.abs() * 0.5)
+        .max(mid.abs() * 0.001)
+        .max(f64::EPSILON);
+    let (x_min, x_max) = (mid - reach, mid + reach);
+    let mid_line = [(mid, 0.0), (mid, max_cum)];
+
+    let datasets = vec![
+        Dataset::default()
+            .name("asks")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Green))
+            .data(&ask_pts),
+        Dataset::default()
+            .name("bids")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Red))
+            .data(&bid_pts),
+        Dataset::default()
+            .name("mid")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&mid_line),
+    ];
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .legend_position(None)
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([x_min, x_max])
+                .labels(vec![
+                    Span::raw(fmt_price(x_min)),
+                    Span::raw(fmt_price(mid)),
+                    Span::raw(fmt_price(x_max)),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([0.0, max_cum * 1.05])
+                .labels(vec![
+                    Span::raw("0"),
+                    Span::raw(fmt_compact((max_cum / 2.0) as i64)),
+                    Span::raw(fmt_compact(max_cum as i64)),
+                ]),
+        );
+    frame.render_widget(chart, area);
 }
 
 fn colored_price(v: f64, color: Color) -> Cell<'static> {
     Cell::from(fmt_price(v)).style(Style::default().fg(color))
 }
 
+/// The game's rarity colours, as near as the terminal palette gets.
+fn rarity_color(rarity: Rarity) -> Color {
+    match rarity {
+        Rarity::Common => Color::White,
+        Rarity::Uncommon => Color::Green,
+        Rarity::Rare => Color::Blue,
+        Rarity::Epic => Color::Magenta,
+        Rarity::Legendary => Color::Yellow,
+        Rarity::Mythic => Color::LightMagenta,
+        Rarity::Divine => Color::Cyan,
+        Rarity::Special => Color::Red,
+    }
+}
+
+/// Green when `lean` is past `threshold` towards buyers, red towards sellers.
+fn pressure_color(lean: f64, threshold: f64) -> Color {
+    if lean > threshold {
+        Color::Green
+    } else if lean < -threshold {
+        Color::Red
+    } else {
+        Color::Gray
+    }
+}
+
 /// Green above 5%, yellow above 1%; below that is noise after the 1.25% tax.
 fn spread_color(pct: f64) -> Color {
     if pct >= 5.0 {
@@ -394,23 +1810,8 @@ fn spread_color(pct: f64) -> Color {
     }
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.chars().count() <= max {
-        s.to_string()
-    } else {
-        s.chars().take(max.saturating_sub(1)).collect::<String>() + "…"
-    }
-}
-
 fn age_label(last_updated_ms: i64) -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    let now_ms = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_millis() as i64)
-        .unwrap_or(0);
-
-    match now_ms.checked_sub(last_updated_ms) {
+    match now_ms().checked_sub(last_updated_ms) {
         Some(ms) if (0..60_000).contains(&ms) => format!("{}s old", ms / 1000),
         Some(ms) if ms >= 60_000 => format!("{}m old", ms / 60_000),
         _ => "just now".to_string(),
@@ -418,40 +1819,48 @@ fn age_label(last_updated_ms: i64) -> String {
 }
 
 
-const SMA_WINDOW: usize = 5;
-
-/// `(seconds since first sample, value)` points.
-type Series = Vec<(f64, f64)>;
-
 /// Draws price history as two panes, each scaled to its own series so small
-/// moves stay visible despite the ask sitting well above the bid.
+/// moves stay visible despite the ask sitting well above the bid, and the
+/// spread under them when it is switched on.
 fn draw_history_chart(frame: &mut Frame, area: Rect, app: &App) {
-    let (pts_buy, pts_sell) = history_series(app);
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1),
+    let window = chart_window(app);
+    let (pts_buy, pts_sell) = history_series(app, window);
+
+    let panes = if app.detail.show_spread {
+        [
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+            Constraint::Percentage(30),
+        ]
+    } else {
+        [
             Constraint::Percentage(50),
             Constraint::Percentage(50),
-        ])
+            Constraint::Length(0),
+        ]
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), panes[0], panes[1], panes[2]])
         .split(area);
 
     draw_history_legend(frame, chunks[0], app);
 
-    let max_x = pts_buy.last().map(|p| p.0).unwrap_or(1.0).max(1.0);
+    let max_x = window.map_or(1.0, |w| (w.span_ms() as f64 / 1000.0).max(1.0));
     let percent = app.detail.show_percent;
 
-    let (buy_sma, sell_sma) = if app.detail.show_sma {
-        (sma(&pts_buy, SMA_WINDOW), sma(&pts_sell, SMA_WINDOW))
-    } else {
-        (Vec::new(), Vec::new())
-    };
-    let (buy_mid, sell_mid) = if app.detail.show_midline {
-        (mean_line(&pts_buy, max_x), mean_line(&pts_sell, max_x))
-    } else {
-        (Vec::new(), Vec::new())
-    };
+    if app.detail.show_candles {
+        draw_candle_pane(frame, chunks[1], app, window, "Instant Buy (ask)", |s| {
+            s.buy
+        });
+        draw_candle_pane(frame, chunks[2], app, window, "Instant Sell (bid)", |s| {
+            s.sell
+        });
+        if app.detail.show_spread {
+            draw_spread_pane(frame, chunks[3], app, window, max_x);
+        }
+        return;
+    }
 
     draw_price_pane(
         frame,
@@ -459,10 +1868,9 @@ fn draw_history_chart(frame: &mut Frame, area: Rect, app: &App) {
         PaneSpec {
             title: "Instant Buy (ask)",
             color: Color::Green,
-            sma_color: Color::LightGreen,
             pts: &pts_buy,
-            sma: &buy_sma,
-            mid: &buy_mid,
+            overlays: &pane_overlays(app, &pts_buy, max_x, Color::LightGreen),
+            window,
             max_x,
             percent,
         },
@@ -473,27 +1881,437 @@ fn draw_history_chart(frame: &mut Frame, area: Rect, app: &App) {
         PaneSpec {
             title: "Instant Sell (bid)",
             color: Color::Red,
-            sma_color: Color::LightRed,
             pts: &pts_sell,
-            sma: &sell_sma,
-            mid: &sell_mid,
+            overlays: &pane_overlays(app, &pts_sell, max_x, Color::LightRed),
+            window,
             max_x,
             percent,
         },
     );
+    if app.detail.show_spread {
+        draw_spread_pane(frame, chunks[3], app, window, max_x);
+    }
+}
+
+/// Spread over the visible history, in % of the bid in % mode, with where
+/// the current spread ranks among the rest.
+fn draw_spread_pane(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    window: Option<ChartWindow>,
+    max_x: f64,
+) {
+    let percent = app.detail.show_percent;
+    let fmt_y = |v: f64| if percent { fmt_pct(v) } else { fmt_price(v) };
+    let pts: Series = visible_history(app, window)
+        .map(|(x, s)| {
+            let px = s.prices();
+            (
+                x,
+                if percent {
+                    px.spread_pct()
+                } else {
+                    px.spread()
+                },
+            )
+        })
+        .collect();
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut title = vec![Span::styled(
+        "Spread ",
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    let stats = overlays::stats(&pts);
+    match stats {
+        Some(st) => {
+            // Wide spreads are the interesting ones, so the top of the range is hot.
+            let rank_color = if st.percentile >= 80.0 {
+                Color::Green
+            } else if st.percentile <= 20.0 {
+                Color::DarkGray
+            } else {
+                Color::Yellow
+            };
+            title.extend([
+                Span::styled(fmt_y(st.current), Style::default().fg(Color::Yellow)),
+                Span::styled("   mean ", dim),
+                Span::raw(fmt_y(st.mean)),
+                Span::styled("  min ", dim),
+                Span::raw(fmt_y(st.min)),
+                Span::styled("  max ", dim),
+                Span::raw(fmt_y(st.max)),
+                Span::styled("  pct ", dim),
+                Span::styled(
+                    format!("{:.0}", st.percentile),
+                    Style::default().fg(rank_color),
+                ),
+            ]);
+        }
+        None => title.push(Span::styled("—", dim)),
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL);
+
+    let Some(st) = stats.filter(|_| pts.len() >= 2) else {
+        frame.render_widget(
+            Paragraph::new(Span::styled("collecting samples…", dim)).block(block),
+            area,
+        );
+        return;
+    };
+
+    let mean = [(0.0, st.mean), (max_x, st.mean)];
+    let [y_min, y_max] = auto_bounds(&pts);
+    let drawn = downsample(&pts, area.width as usize);
+    let chart = Chart::new(vec![
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&drawn),
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Gray))
+            .data(&mean),
+    ])
+    .block(block)
+    .legend_position(None)
+    .x_axis(time_axis(window, max_x))
+    .y_axis(
+        Axis::default()
+            .style(dim)
+            .bounds([y_min, y_max])
+            .labels(vec![Span::raw(fmt_y(y_min)), Span::raw(fmt_y(y_max))]),
+    );
+    frame.render_widget(chart, area);
+}
+
+fn draw_compare_table(frame: &mut Frame, app: &App, area: Rect) {
+    let ids = &app.compare.marked;
+    let products: Vec<Option<&BazaarProduct>> =
+        ids.iter().map(|id| app.data.products.get(id)).collect();
+    let dim = Style::default().fg(Color::DarkGray);
+    let missing = || Cell::from(Line::from("—").right_aligned()).style(dim);
+
+    let header = Row::new(
+        std::iter::once(Cell::from("")).chain(ids.iter().enumerate().map(|(i, id)| {
+            let mut style = Style::default()
+                .fg(COMPARE_COLORS[i])
+                .add_modifier(Modifier::BOLD);
+            if i == app.compare.selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Cell::from(Line::from(app.display_name(id).to_string()).right_aligned()).style(style)
+        })),
+    );
+
+    let mut rows: Vec<Row> = Column::ALL
+        .iter()
+        .map(|&column| {
+            let cells = ids.iter().zip(&products).map(|(id, p)| match p {
+                Some(p) => {
+                    Cell::from(Line::from(column_cell(column, p, app.flip(id))).right_aligned())
+                }
+                None => missing(),
+            });
+            Row::new(std::iter::once(Cell::from(column.label()).style(dim)).chain(cells))
+        })
+        .collect();
+    rows.push(Row::new(vec![Cell::from("")]));
+    let labels = [
+        "Buy Vol",
+        "Sell Vol",
+        "Buy Move/Wk",
+        "Sell Move/Wk",
+        "Buy Orders",
+        "Sell Orders",
+    ];
+    let quick = |q: &BazaarQuickStatus| {
+        [
+            q.buy_volume,
+            q.sell_volume,
+            q.buy_moving_week,
+            q.sell_moving_week,
+            q.buy_orders,
+            q.sell_orders,
+        ]
+    };
+    for (i, label) in labels.into_iter().enumerate() {
+        let cells = products
+            .iter()
+            .map(|p| match p.and_then(|p| p.quick_status.as_ref()) {
+                Some(q) => Cell::from(Line::from(fmt_count(quick(q)[i])).right_aligned()),
+                None => missing(),
+            });
+        rows.push(Row::new(
+            std::iter::once(Cell::from(label).style(dim)).chain(cells),
+        ));
+    }
+
+    let widths =
+        std::iter::once(Constraint::Length(12)).chain(ids.iter().map(|_| Constraint::Fill(1)));
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .title(Line::from(vec![
+                Span::styled("Compare ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    format!("({} products)", ids.len()),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]))
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(table, area);
+}
+
+/// Every marked product's mid price as % change since the first moment
+/// they all have history, so the lines share a starting point.
+fn draw_compare_chart(frame: &mut Frame, app: &App, area: Rect) {
+    let histories = &app.compare.history;
+    let start = histories
+        .iter()
+        .filter_map(|h| h.front())
+        .map(|s| s.ts)
+        .max();
+    let end = histories
+        .iter()
+        .filter_map(|h| h.back())
+        .map(|s| s.ts)
+        .max();
+    let window = start.zip(end).map(|(start, end)| ChartWindow {
+        start,
+        end: end.max(start + 1),
+    });
+    let max_x = window.map_or(1.0, |w| (w.span_ms() as f64 / 1000.0).max(1.0));
+
+    let mid = |s: &Sample| (s.buy + s.sell) / 2.0;
+    let series: Vec<Series> = histories
+        .iter()
+        .map(|h| {
+            let Some(w) = window else {
+                return Vec::new();
+            };
+            let from = h.partition_point(|s| s.ts < w.start);
+            let base = h.get(from).map_or(0.0, mid);
+            let pts: Series = h
+                .range(from..)
+                .map(|s| ((s.ts - w.start) as f64 / 1000.0, rebase(mid(s), base)))
+                .collect();
+            downsample(&pts, area.width as usize)
+        })
+        .collect();
+
+    let mut title = vec![Span::styled(
+        "Mid price, % change ",
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    for (i, (id, pts)) in app.compare.marked.iter().zip(&series).enumerate() {
+        title.push(Span::styled("  ──", Style::default().fg(COMPARE_COLORS[i])));
+        title.push(Span::styled(
+            format!(" {} ", app.display_name(id)),
+            Style::default().fg(Color::DarkGray),
+        ));
+        title.push(Span::styled(
+            pts.last().map_or_else(|| "—".to_string(), |p| fmt_pct(p.1)),
+            Style::default().fg(COMPARE_COLORS[i]),
+        ));
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL);
+
+    let all: Series = series.iter().flatten().copied().collect();
+    if all.len() < 2 {
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                "collecting samples…",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .block(block),
+            area,
+        );
+        return;
+    }
+
+    let [y_min, y_max] = auto_bounds(&all);
+    let datasets = series
+        .iter()
+        .enumerate()
+        .map(|(i, pts)| {
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(COMPARE_COLORS[i]))
+                .data(pts)
+        })
+        .collect();
+    let chart = Chart::new(datasets)
+        .block(block)
+        .legend_position(None)
+        .x_axis(time_axis(window, max_x))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([y_min, y_max])
+                .labels(vec![
+                    Span::raw(fmt_pct(y_min)),
+                    Span::raw(fmt_pct((y_min + y_max) / 2.0)),
+                    Span::raw(fmt_pct(y_max)),
+                ]),
+        );
+    frame.render_widget(chart, area);
+}
+
+/// A price pane as OHLC candles of the window's samples, at the chosen
+/// interval or the shortest that fits.
+fn draw_candle_pane(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    window: Option<ChartWindow>,
+    title: &str,
+    price: fn(&Sample) -> f64,
+) {
+    let percent = app.detail.show_percent;
+    let fmt_y = |v: f64| if percent { fmt_pct(v) } else { fmt_price(v) };
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut first = None;
+    let points = visible_history(app, window).map(|(_, s)| {
+        let v = price(s);
+        let base = *first.get_or_insert(v);
+        (s.ts, if percent { rebase(v, base) } else { v })
+    });
+    // Axis labels are about this wide; the widget drops any that don't fit.
+    let slots = CandleChart::capacity(area.width, 10);
+    let interval = app
+        .detail
+        .candle_interval
+        .unwrap_or_else(|| auto_interval(window.map_or(0, |w| w.span_ms()), slots));
+    let (interval_ms, interval_label) = CANDLE_INTERVALS[interval];
+    let all = candles(points, interval_ms);
+    let shown = &all[all.len().saturating_sub(slots)..];
+
+    let mut spans = vec![Span::styled(
+        format!("{title} "),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if let Some(c) = shown.last() {
+        let color = if c.rising() { Color::Green } else { Color::Red };
+        for (name, v) in [("O", c.open), ("H", c.high), ("L", c.low), ("C", c.close)] {
+            spans.push(Span::styled(format!("{name} "), dim));
+            spans.push(Span::styled(
+                format!("{}  ", fmt_y(v)),
+                Style::default().fg(color),
+            ));
+        }
+    }
+    spans.push(Span::styled(format!("[{interval_label} candles]"), dim));
+    let block = Block::default()
+        .title(Line::from(spans))
+        .borders(Borders::ALL);
+
+    let (Some(first), Some(last)) = (shown.first(), shown.last()) else {
+        frame.render_widget(
+            Paragraph::new(Span::styled("collecting samples…", dim)).block(block),
+            area,
+        );
+        return;
+    };
+    let extremes: Series = shown
+        .iter()
+        .flat_map(|c| [(0.0, c.low), (0.0, c.high)])
+        .collect();
+    let [y_min, y_max] = auto_bounds(&extremes);
+    let span = last.ts - first.ts + interval_ms;
+    let chart = CandleChart::new(shown, [y_min, y_max])
+        .block(block)
+        .y_labels([y_min, (y_min + y_max) / 2.0, y_max].map(fmt_y))
+        .x_labels([first.ts, last.ts].map(|ms| fmt_axis_time(ms, span)));
+    frame.render_widget(chart, area);
 }
 
 struct PaneSpec<'a> {
     title: &'a str,
     color: Color,
-    sma_color: Color,
     pts: &'a [(f64, f64)],
-    sma: &'a [(f64, f64)],
-    mid: &'a [(f64, f64)],
+    overlays: &'a [Overlay],
+    window: Option<ChartWindow>,
     max_x: f64,
     percent: bool,
 }
 
+/// One overlay on a price pane: a single line, or a pair of bands.
+struct Overlay {
+    label: String,
+    color: Color,
+    lines: Vec<Series>,
+}
+
+/// The overlays switched on in the detail view, computed over `pts`.
+fn pane_overlays(app: &App, pts: &[(f64, f64)], max_x: f64, sma_color: Color) -> Vec<Overlay> {
+    let config = &app.config;
+    let mut out = Vec::new();
+    let mut add = |on: bool, label: String, color: Color, lines: Vec<Series>| {
+        if on && lines.iter().all(|l| !l.is_empty()) {
+            out.push(Overlay {
+                label,
+                color,
+                lines,
+            });
+        }
+    };
+
+    add(
+        app.detail.show_sma,
+        format!("SMA({})", config.sma_window),
+        sma_color,
+        vec![overlays::sma(pts, config.sma_window)],
+    );
+    add(
+        app.detail.show_ema,
+        format!("EMA({})", config.ema_window),
+        Color::Yellow,
+        vec![overlays::ema(pts, config.ema_window)],
+    );
+    if app.detail.show_bollinger {
+        let (upper, lower) =
+            overlays::bollinger(pts, config.bollinger_window, config.bollinger_width);
+        add(
+            true,
+            format!("BB({},{})", config.bollinger_window, config.bollinger_width),
+            Color::Magenta,
+            vec![lower, upper],
+        );
+    }
+    add(
+        app.detail.show_twap,
+        format!("TWAP({})", config.twap_window),
+        Color::Cyan,
+        vec![overlays::twap(pts, config.twap_window)],
+    );
+    if app.detail.show_envelope {
+        let (hi, lo) = overlays::envelope(pts, config.envelope_window);
+        add(
+            true,
+            format!("env({})", config.envelope_window),
+            Color::Blue,
+            vec![lo, hi],
+        );
+    }
+    add(
+        app.detail.show_midline,
+        "mean".into(),
+        Color::Gray,
+        vec![mean_line(pts, max_x)],
+    );
+    out
+}
+
 fn draw_price_pane(frame: &mut Frame, area: Rect, spec: PaneSpec<'_>) {
     let fmt_y = |v: f64| if spec.percent { fmt_pct(v) } else { fmt_price(v) };
 
@@ -511,16 +2329,22 @@ fn draw_price_pane(frame: &mut Frame, area: Rect, spec: PaneSpec<'_>) {
         Span::styled("──", Style::default().fg(spec.color)),
         Span::styled(" price", Style::default().fg(Color::DarkGray)),
     ];
-    if !spec.sma.is_empty() {
-        title.push(Span::styled("  ──", Style::default().fg(spec.sma_color)));
+    // Each overlay's latest value, bands as low–high.
+    for overlay in spec.overlays {
+        let values: Vec<String> = overlay
+            .lines
+            .iter()
+            .filter_map(|line| line.last().map(|p| fmt_y(p.1)))
+            .collect();
+        title.push(Span::styled("  ──", Style::default().fg(overlay.color)));
         title.push(Span::styled(
-            format!(" SMA({SMA_WINDOW})"),
+            format!(" {} ", overlay.label),
             Style::default().fg(Color::DarkGray),
         ));
-    }
-    if !spec.mid.is_empty() {
-        title.push(Span::styled("  ──", Style::default().fg(Color::Gray)));
-        title.push(Span::styled(" mean", Style::default().fg(Color::DarkGray)));
+        title.push(Span::styled(
+            values.join("–"),
+            Style::default().fg(overlay.color),
+        ));
     }
 
     let block = Block::default().title(Line::from(title)).borders(Borders::ALL);
@@ -537,7 +2361,27 @@ fn draw_price_pane(frame: &mut Frame, area: Rect, spec: PaneSpec<'_>) {
         return;
     }
 
-    let [y_min, y_max] = auto_bounds(spec.pts);
+    // Bands can reach past the price, so they count towards the scale.
+    let mut scaled: Vec<(f64, f64)> = spec.pts.to_vec();
+    for overlay in spec.overlays {
+        for line in &overlay.lines {
+            scaled.extend_from_slice(line);
+        }
+    }
+    let [y_min, y_max] = auto_bounds(&scaled);
+
+    // Long ranges hold far more samples than the pane has columns.
+    let buckets = area.width as usize;
+    let price = downsample(spec.pts, buckets);
+    let lines: Vec<(&Overlay, Series)> = spec
+        .overlays
+        .iter()
+        .flat_map(|o| {
+            o.lines
+                .iter()
+                .map(move |line| (o, downsample(line, buckets)))
+        })
+        .collect();
 
     let mut datasets = vec![
         Dataset::default()
@@ -545,26 +2389,16 @@ fn draw_price_pane(frame: &mut Frame, area: Rect, spec: PaneSpec<'_>) {
             .marker(symbols::Marker::Braille)
             .graph_type(GraphType::Line)
             .style(Style::default().fg(spec.color))
-            .data(spec.pts),
+            .data(&price),
     ];
-    if !spec.sma.is_empty() {
-        datasets.push(
-            Dataset::default()
-                .name(format!("SMA({SMA_WINDOW})"))
-                .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
-                .style(Style::default().fg(spec.sma_color))
-                .data(spec.sma),
-        );
-    }
-    if !spec.mid.is_empty() {
+    for (overlay, line) in &lines {
         datasets.push(
             Dataset::default()
-                .name("mean")
+                .name(overlay.label.clone())
                 .marker(symbols::Marker::Braille)
                 .graph_type(GraphType::Line)
-                .style(Style::default().fg(Color::Gray))
-                .data(spec.mid),
+                .style(Style::default().fg(overlay.color))
+                .data(line),
         );
     }
 
@@ -572,16 +2406,7 @@ fn draw_price_pane(frame: &mut Frame, area: Rect, spec: PaneSpec<'_>) {
     let chart = Chart::new(datasets)
         .block(block)
         .legend_position(None)
-        .x_axis(
-            Axis::default()
-                .style(Style::default().fg(Color::DarkGray))
-                .bounds([0.0, spec.max_x])
-                .labels(vec![
-                    Span::raw("0s"),
-                    Span::raw(format!("{:.0}s", spec.max_x / 2.0)),
-                    Span::raw(format!("{:.0}s", spec.max_x)),
-                ]),
-        )
+        .x_axis(time_axis(spec.window, spec.max_x))
         .y_axis(
             Axis::default()
                 .style(Style::default().fg(Color::DarkGray))
@@ -631,51 +2456,87 @@ fn draw_history_legend(frame: &mut Frame, area: Rect, app: &App) {
             Style::default().fg(Color::DarkGray),
         ));
     }
+    let mut view = vec![app.detail.range.label().to_string()];
+    if app.detail.zoom > 0 {
+        view.push(format!(
+            "zoom {:.0}%",
+            CHART_ZOOM_STEP.powi(app.detail.zoom as i32) * 100.0
+        ));
+    }
+    if app.detail.pan > 0 {
+        view.push("panned".into());
+    }
+    let moved = app.detail.zoom > 0 || app.detail.pan > 0;
+    spans.push(Span::styled(
+        format!("   [{}]", view.join(" · ")),
+        Style::default().fg(if moved {
+            Color::Yellow
+        } else {
+            Color::DarkGray
+        }),
+    ));
 
     frame.render_widget(Paragraph::new(Line::from(spans)), area);
 }
 
-/// Sample history as two series: absolute coins, or percent change from first.
-fn history_series(app: &App) -> (Series, Series) {
+fn chart_window(app: &App) -> Option<ChartWindow> {
+    let d = &app.detail;
+    ChartWindow::new(&d.history, d.range, d.zoom, d.pan)
+}
+
+/// The samples in `window`, with seconds since it starts.
+fn visible_history(app: &App, window: Option<ChartWindow>) -> impl Iterator<Item = (f64, &Sample)> {
+    let history = &app.detail.history;
+    let (range, start) = window.map_or((0..0, 0), |w| (w.visible(history), w.start));
+    history
+        .range(range)
+        .map(move |s| ((s.ts - start) as f64 / 1000.0, s))
+}
+
+/// Wall-clock labels at the start, middle and end of the window.
+fn time_axis(window: Option<ChartWindow>, max_x: f64) -> Axis<'static> {
+    let axis = Axis::default()
+        .style(Style::default().fg(Color::DarkGray))
+        .bounds([0.0, max_x]);
+    match window {
+        Some(w) => {
+            let span = w.span_ms();
+            axis.labels(
+                [w.start, w.start + span / 2, w.end].map(|ms| Span::raw(fmt_axis_time(ms, span))),
+            )
+        }
+        None => axis,
+    }
+}
+
+/// Sample history as two series: absolute coins, or percent change from the
+/// first sample in the zoomed window.
+fn history_series(app: &App, window: Option<ChartWindow>) -> (Series, Series) {
     let mut pts_buy = Vec::with_capacity(app.detail.history.len());
     let mut pts_sell = Vec::with_capacity(app.detail.history.len());
 
-    let Some(&(t0, b0, s0)) = app.detail.history.front() else {
-        return (pts_buy, pts_sell);
-    };
-
-    for (t, b, s) in app.detail.history.iter() {
-        let x = (*t - t0).as_secs_f64();
+    let mut first = None;
+    for (x, s) in visible_history(app, window) {
+        let first = *first.get_or_insert(s);
         if app.detail.show_percent {
-            let rebase =
-                |v: f64, base: f64| if base != 0.0 { (v - base) / base * 100.0 } else { 0.0 };
-            pts_buy.push((x, rebase(*b, b0)));
-            pts_sell.push((x, rebase(*s, s0)));
+            pts_buy.push((x, rebase(s.buy, first.buy)));
+            pts_sell.push((x, rebase(s.sell, first.sell)));
         } else {
-            pts_buy.push((x, *b));
-            pts_sell.push((x, *s));
+            pts_buy.push((x, s.buy));
+            pts_sell.push((x, s.sell));
         }
     }
 
     (pts_buy, pts_sell)
 }
 
-fn sma(pts: &[(f64, f64)], k: usize) -> Series {
-    if k == 0 || pts.len() < k {
-        return Vec::new();
-    }
-    let mut out = Vec::with_capacity(pts.len() - k + 1);
-    let mut sum = 0.0;
-    for (i, p) in pts.iter().enumerate() {
-        sum += p.1;
-        if i >= k {
-            sum -= pts[i - k].1;
-        }
-        if i + 1 >= k {
-            out.push((p.0, sum / k as f64));
-        }
+/// Percent change of `v` from `base`.
+fn rebase(v: f64, base: f64) -> f64 {
+    if base != 0.0 {
+        (v - base) / base * 100.0
+    } else {
+        0.0
     }
-    out
 }
 
 fn mean_line(pts: &[(f64, f64)], max_x: f64) -> Series {
@@ -1,3 +1,6 @@
+use chrono::{Local, TimeZone};
+use std::path::PathBuf;
+
 /// Returns a prettified display name from a bazaar product id.
 pub fn pretty_name(id: &str) -> String {
     let mut parts = id.split(':');
@@ -21,6 +24,54 @@ pub fn pretty_name(id: &str) -> String {
     }
 }
 
+/// `<platform config dir>/hypixel-bazaar-tui/<name>`, if the platform has one.
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("hypixel-bazaar-tui").join(name))
+}
+
+/// Wall-clock Unix milliseconds, the unit the API stamps snapshots with.
+pub fn now_ms() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Local wall-clock `HH:MM:SS` for a Unix ms timestamp.
+pub fn fmt_time(ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(ms)
+        .single()
+        .map_or_else(|| "-".to_string(), |t| t.format("%H:%M:%S").to_string())
+}
+
+/// Local wall-clock label for a chart axis `span_ms` wide: seconds only on
+/// short spans, the date only on ones past a day.
+pub fn fmt_axis_time(ms: i64, span_ms: i64) -> String {
+    let format = if span_ms > 24 * 3_600_000 {
+        "%b %d %H:%M"
+    } else if span_ms > 10 * 60_000 {
+        "%H:%M"
+    } else {
+        "%H:%M:%S"
+    };
+    Local
+        .timestamp_millis_opt(ms)
+        .single()
+        .map_or_else(|| "-".to_string(), |t| t.format(format).to_string())
+}
+
+/// Cuts `s` to at most `max` characters, ending in `…` when it was longer.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        s.chars().take(max.saturating_sub(1)).collect::<String>() + "…"
+    }
+}
+
 /// Normalizes a string for fuzzy matching and indexing.
 pub fn normalize(s: &str) -> String {
     s.to_ascii_lowercase()
@@ -125,6 +176,13 @@ mod tests {
         assert_eq!(fmt_price(-12_345_678.0), "-12,345,678");
     }
 
+    #[test]
+    fn long_names_are_cut_to_width() {
+        assert_eq!(truncate("Enchanted Diamond", 20), "Enchanted Diamond");
+        assert_eq!(truncate("Enchanted Diamond Block", 12), "Enchanted D…");
+        assert_eq!(truncate("Enchanted Diamond Block", 12).chars().count(), 12);
+    }
+
     #[test]
     fn counts_are_grouped_exactly() {
         assert_eq!(fmt_count(0), "0");